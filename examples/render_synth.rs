@@ -0,0 +1,28 @@
+// Renders every synthesised sound to WAV files, to listen to them without
+// starting the game: cargo run --example render_synth -- <out dir>
+#[path = "../src/synth.rs"]
+mod synth;
+
+use std::path::PathBuf;
+
+fn main() {
+    let dir = PathBuf::from(std::env::args().nth(1).unwrap_or_else(|| "synth".to_string()));
+    std::fs::create_dir_all(&dir).expect("Can't create the output dir");
+
+    let mut sounds = vec![
+        ("menu_move".to_string(), synth::menu_move()),
+        ("menu_select".to_string(), synth::menu_select()),
+        ("turn".to_string(), synth::turn()),
+        ("pause".to_string(), synth::pause()),
+        ("game_over".to_string(), synth::game_over()),
+        ("music".to_string(), synth::music()),
+    ];
+    for length in [2, 20, 50] {
+        sounds.push((format!("eat_{}", length), synth::eat(length)));
+    }
+    for (name, samples) in sounds {
+        let path = dir.join(format!("{}.wav", name));
+        std::fs::write(&path, synth::to_wav(&samples)).expect("Can't write the WAV file");
+        println!("{}", path.display());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::config::Config;
+use crate::play_state::{
+    campaign::{campaign_level, CAMPAIGN_LENGTH},
+    level::{CurrentLevel, Level},
+    replay::{PlayBack, Replay, REPLAY_FILE},
+    survival::survival_level,
+    GameMode, GameRng, Rules,
+};
+use crate::tournament;
+use bevy::prelude::*;
+
+const USAGE: &str = "\
+Usage: snaky [OPTIONS]
+       snaky tournament --help
+
+Options:
+  --seed <N>          Pick the same food every run
+  --mode <MODE>       Start a run right away: classic, timed, survival or
+                      campaign:<N> for the Nth campaign level
+  --level <FILE>      Start a run right away on a level saved by the editor
+  --arena <W>x<H>     Size of the classic and timed arena, like 60x40
+  --speed <N>         Steps the snake makes every second
+  --windowed          Start in a window
+  --fullscreen        Start in fullscreen
+  --skip-menu         Start a run right away, classic unless picked otherwise
+  --replay <FILE>     Watch a recorded run, with its own mode, level, seed
+                      and speed
+  -h, --help          Show this and quit
+
+Options win over the config file. The last run is recorded in the data dir,
+like ~/.local/share/snaky/last_replay.ron";
+
+// What the game was launched with. Anything missing is left to the config
+// file and the menus.
+#[derive(Debug, Default)]
+pub struct Launch {
+    pub seed: Option<u64>,
+    pub mode: Option<GameMode>,
+    pub level: Option<Level>,
+    pub skip_menu: bool,
+    pub replay: Option<Replay>,
+    // Config keys set on top of the config file
+    pub overrides: Vec<(&'static str, ron::Value)>,
+}
+impl Launch {
+    // Checked right away, with the same limits as the config file
+    fn set(&mut self, flag: &str, key: &'static str, value: ron::Value) -> Result<(), String> {
+        check(flag, key, value.clone())?;
+        self.overrides.push((key, value));
+        Ok(())
+    }
+}
+
+// Against the same limits as the config file
+fn check(flag: &str, key: &str, value: ron::Value) -> Result<(), String> {
+    Config::default()
+        .set(key, value)
+        .map_err(|e| format!("{}: `{}` {}", flag, key, e))
+}
+
+// Added first, the config and the window are set up from the launch options
+pub struct CliPlugin;
+impl Plugin for CliPlugin {
+    fn build(&self, app: &mut App) {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        // Tournaments are played headless, the game never starts
+        if args.first().map(|arg| arg.as_str()) == Some("tournament") {
+            std::process::exit(tournament::run(&args[1..]));
+        }
+        if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+        let launch = match parse(&args) {
+            Ok(launch) => launch,
+            Err(e) => {
+                eprintln!("snaky: {}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        };
+        app.insert_resource(launch).add_startup_system(apply_launch);
+    }
+}
+
+fn parse(args: &[String]) -> Result<Launch, String> {
+    let mut launch = Launch::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        // Values come after a space or an equals sign
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .or_else(|| args.next().map(|arg| arg.as_str()))
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        match flag {
+            "--seed" => {
+                let seed = value()?;
+                launch.seed = Some(
+                    seed.parse()
+                        .map_err(|_| format!("{} must be a whole number, got {}", flag, seed))?,
+                );
+            }
+            "--mode" => launch.mode = Some(parse_mode(value()?)?),
+            "--level" => launch.level = Some(read_level(value()?)?),
+            "--arena" => {
+                let (width, height) = parse_arena(flag, value()?)?;
+                launch.set(flag, "arena_width", whole_number(width))?;
+                launch.set(flag, "arena_height", whole_number(height))?;
+            }
+            "--speed" => {
+                let speed = value()?;
+                let steps: f64 = match speed.parse() {
+                    Ok(steps) if (1.0..=50.0).contains(&steps) => steps,
+                    _ => return Err(format!("{} must be from 1 to 50, got {}", flag, speed)),
+                };
+                launch.set(
+                    flag,
+                    "tick_step",
+                    ron::Value::Number(ron::Number::new(1.0 / steps)),
+                )?;
+            }
+            "--windowed" | "--fullscreen" if inline.is_none() => {
+                let fullscreen = flag == "--fullscreen";
+                launch.set(flag, "fullscreen", ron::Value::Bool(fullscreen))?;
+            }
+            "--skip-menu" if inline.is_none() => launch.skip_menu = true,
+            "--replay" => launch.replay = Some(read_replay(value()?)?),
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    // A replay brings its own run, set last so it wins over the other options
+    if let Some(replay) = launch.replay.clone() {
+        launch.mode = Some(replay.mode);
+        launch.level = Some(replay.level);
+        // Its speed and length are set for its own run only, see `apply_launch`
+        let tick_step = ron::Value::Number(ron::Number::new(replay.tick_step));
+        check("--replay", "tick_step", tick_step)?;
+        let starting_length = ron::Value::Number(ron::Number::new(replay.starting_length as i64));
+        check("--replay", "starting_length", starting_length)?;
+    }
+    // There is nothing else to use them for
+    launch.skip_menu |= launch.mode.is_some() || launch.level.is_some();
+    Ok(launch)
+}
+
+fn parse_mode(mode: &str) -> Result<GameMode, String> {
+    match mode {
+        "classic" => Ok(GameMode::Classic),
+        "timed" => Ok(GameMode::TimeAttack),
+        "survival" => Ok(GameMode::Survival),
+        _ => match mode.strip_prefix("campaign:").map(str::parse::<usize>) {
+            Some(Ok(number)) if (1..=CAMPAIGN_LENGTH).contains(&number) => {
+                Ok(GameMode::Campaign(number - 1))
+            }
+            Some(_) => Err(format!(
+                "--mode campaign needs a level from 1 to {}, got {}",
+                CAMPAIGN_LENGTH, mode
+            )),
+            None => Err(format!("--mode has no mode {}", mode)),
+        },
+    }
+}
+
+fn whole_number(value: i32) -> ron::Value {
+    ron::Value::Number(ron::Number::new(value))
+}
+
+// Like `60x40`, with the same limits as the config file
+pub fn parse_arena(flag: &str, arena: &str) -> Result<(i32, i32), String> {
+    let size = arena
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+    let (width, height) = match size {
+        Some(size) => size,
+        None => return Err(format!("{} must look like 60x40, got {}", flag, arena)),
+    };
+    check(flag, "arena_width", whole_number(width))?;
+    check(flag, "arena_height", whole_number(height))?;
+    Ok((width, height))
+}
+
+pub fn read_level(path: &str) -> Result<Level, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("--level: can't read {}: {}", path, e))?;
+    Level::from_ron(&source).map_err(|e| {
+        format!(
+            "--level: {}:{}:{}: {}",
+            path, e.position.line, e.position.col, e.code
+        )
+    })
+}
+
+fn read_replay(path: &str) -> Result<Replay, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("--replay: can't read {}: {}", path, e))?;
+    ron::from_str(&source).map_err(|e| {
+        format!(
+            "--replay: {}:{}:{}: {}, expected a run recorded as {}",
+            path, e.position.line, e.position.col, e.code, REPLAY_FILE
+        )
+    })
+}
+
+// Sets up the run the loading screen starts instead of the main menu
+fn apply_launch(
+    mut commands: Commands,
+    mut launch: ResMut<Launch>,
+    mut config: ResMut<Config>,
+    mut rng: ResMut<GameRng>,
+    mut rules: ResMut<Rules>,
+    mut game_mode: ResMut<GameMode>,
+    mut current_level: ResMut<CurrentLevel>,
+) {
+    if launch.seed.is_some() {
+        *rng = GameRng::new(launch.seed);
+    }
+    // Only its own run plays by the replay's settings, see `save_recording`
+    if let Some(replay) = launch.replay.take() {
+        let play_back = PlayBack {
+            config: config.clone(),
+            rules: rules.clone(),
+            replay,
+        };
+        *rng = GameRng::once(play_back.replay.seed);
+        config.tick_step = play_back.replay.tick_step;
+        config.starting_length = play_back.replay.starting_length;
+        rules.tail_cut = play_back.replay.tail_cut;
+        commands.insert_resource(play_back);
+    }
+    if !launch.skip_menu {
+        return;
+    }
+    let mode = launch.mode.unwrap_or(GameMode::Classic);
+    *game_mode = mode;
+    current_level.0 = match (launch.level.take(), mode) {
+        (Some(level), _) => level,
+        (None, GameMode::Survival) => survival_level(),
+        (None, GameMode::Campaign(index)) => campaign_level(index),
+        (None, _) => Level::classic(&config),
+    };
+}
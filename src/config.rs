@@ -0,0 +1,185 @@
+use crate::cli::Launch;
+use bevy::{prelude::*, window::WindowMode};
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+
+pub const CONFIG_FILE: &str = "config.ron";
+
+// Tuning read once at launch from `config.ron` in the user's config dir, like
+// `(tick_step: 0.1, arena_width: 60)`. Every key is optional, a missing or bad
+// one keeps its default. Colours are tuned in the theme files instead.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub window_width: f32,
+    pub window_height: f32,
+    pub fullscreen: bool,
+    // Seconds between two steps of the snake
+    pub tick_step: f64,
+    // Size of the classic and time attack arena
+    pub arena_width: i32,
+    pub arena_height: i32,
+    // Cells the snake covers when it spawns, the head included
+    pub starting_length: usize,
+    // Every font size is multiplied by this
+    pub text_scale: f32,
+}
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            window_width: 800.0,
+            window_height: 600.0,
+            fullscreen: false,
+            tick_step: 0.125,
+            arena_width: 40,
+            arena_height: 30,
+            starting_length: 2,
+            text_scale: 1.0,
+        }
+    }
+}
+impl Config {
+    pub fn set(&mut self, key: &str, value: ron::Value) -> Result<(), String> {
+        match key {
+            "window_width" => self.window_width = ranged(value, 320.0, 7680.0)?,
+            "window_height" => self.window_height = ranged(value, 240.0, 4320.0)?,
+            "fullscreen" => {
+                self.fullscreen = value
+                    .into_rust()
+                    .map_err(|_| "must be true or false".to_string())?
+            }
+            "tick_step" => self.tick_step = ranged(value, 0.02, 1.0)?,
+            "arena_width" => self.arena_width = ranged(value, 5, 200)?,
+            "arena_height" => self.arena_height = ranged(value, 5, 150)?,
+            "starting_length" => self.starting_length = ranged(value, 2, 20)?,
+            "text_scale" => self.text_scale = ranged(value, 0.5, 2.0)?,
+            _ => return Err("is not a known key".to_string()),
+        }
+        Ok(())
+    }
+}
+
+// What a value of the wrong kind is told it should be
+trait Kind {
+    const KIND: &'static str;
+}
+impl Kind for f32 {
+    const KIND: &'static str = "number";
+}
+impl Kind for f64 {
+    const KIND: &'static str = "number";
+}
+impl Kind for i32 {
+    const KIND: &'static str = "whole number";
+}
+impl Kind for usize {
+    const KIND: &'static str = "whole number";
+}
+
+fn ranged<T: DeserializeOwned + PartialOrd + Display + Kind>(
+    value: ron::Value,
+    min: T,
+    max: T,
+) -> Result<T, String> {
+    match value.into_rust::<T>() {
+        Ok(value) if value >= min && value <= max => Ok(value),
+        Ok(value) => Err(format!("must be from {} to {}, got {}", min, max, value)),
+        Err(_) => Err(format!("must be a {} from {} to {}", T::KIND, min, max)),
+    }
+}
+
+// What was wrong with the file, logged once logging is up
+struct ConfigWarnings(Vec<String>);
+
+// Added before the default plugins, the window is sized from the config.
+// Launch options were checked when parsed and win over the file.
+pub struct ConfigPlugin;
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        let (mut config, warnings) = load();
+        if let Some(launch) = app.world.get_resource::<Launch>() {
+            for (key, value) in launch.overrides.iter() {
+                config
+                    .set(key, value.clone())
+                    .expect("HOW DID WE EVEN GET HERE!?!? A launch option passed the check but not the config");
+            }
+        }
+        app.insert_resource(WindowDescriptor {
+            title: "Snaky".to_string(),
+            width: config.window_width,
+            height: config.window_height,
+            mode: if config.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            },
+            ..Default::default()
+        })
+        .insert_resource(config)
+        .insert_resource(ConfigWarnings(warnings))
+        .add_startup_system(report_config);
+    }
+}
+
+fn report_config(warnings: Res<ConfigWarnings>) {
+    for warning in warnings.0.iter() {
+        warn!("{}", warning);
+    }
+}
+
+// Every problem names the file, and the key where there is one
+fn parse(source: &str, file: &str) -> (Config, Vec<String>) {
+    let mut config = Config::default();
+    let mut warnings = Vec::new();
+    let entries = match ron::from_str::<ron::Value>(source) {
+        Ok(ron::Value::Map(entries)) => entries,
+        Ok(_) => {
+            warnings.push(format!(
+                "{}: expected `(key: value, ...)`, using the defaults",
+                file
+            ));
+            return (config, warnings);
+        }
+        Err(e) => {
+            warnings.push(format!(
+                "{}:{}:{}: {}, using the defaults",
+                file, e.position.line, e.position.col, e.code
+            ));
+            return (config, warnings);
+        }
+    };
+    for (key, value) in entries.iter() {
+        let key = match key {
+            ron::Value::String(key) => key,
+            _ => {
+                warnings.push(format!("{}: {:?} is not a key, ignoring it", file, key));
+                continue;
+            }
+        };
+        if let Err(e) = config.set(key, value.clone()) {
+            warnings.push(format!("{}: `{}` {}, ignoring it", file, key, e));
+        }
+    }
+    (config, warnings)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load() -> (Config, Vec<String>) {
+    let path = match dirs::config_dir() {
+        Some(dir) => dir.join("snaky").join(CONFIG_FILE),
+        None => return (Config::default(), Vec::new()),
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(source) => parse(&source, &path.display().to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (Config::default(), Vec::new()),
+        Err(e) => (
+            Config::default(),
+            vec![format!("Can't read config file {}: {}", path.display(), e)],
+        ),
+    }
+}
+
+// There is no file system in wasm
+#[cfg(target_arch = "wasm32")]
+fn load() -> (Config, Vec<String>) {
+    (Config::default(), Vec::new())
+}
@@ -0,0 +1,512 @@
+use super::{storage, AppState, GameSize};
+use crate::config::Config;
+use crate::loading_state::GameAssets;
+use crate::locale::Locale;
+use crate::play_state::{
+    arena::Position,
+    level::{CurrentLevel, Level},
+    GameMode,
+};
+use crate::theme::{Palette, Theme, Themed};
+use bevy::{prelude::*, window::CursorMoved};
+
+const CUSTOM_LEVEL_FILE: &str = "levels/custom.ron";
+
+const MIN_WIDTH: i32 = 5;
+const MAX_WIDTH: i32 = 200;
+const MIN_HEIGHT: i32 = 5;
+const MAX_HEIGHT: i32 = 150;
+// Room left above and below the grid for the HUD
+const GRID_MARGIN: f32 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Wall,
+    Portal,
+    Spawn,
+    Food,
+}
+impl Tool {
+    fn next(&self) -> Tool {
+        match self {
+            Tool::Wall => Tool::Portal,
+            Tool::Portal => Tool::Spawn,
+            Tool::Spawn => Tool::Food,
+            Tool::Food => Tool::Wall,
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Tool::Wall => "editor.tool.wall",
+            Tool::Portal => "editor.tool.portal",
+            Tool::Spawn => "editor.tool.spawn",
+            Tool::Food => "editor.tool.food",
+        }
+    }
+}
+
+struct Editor {
+    level: Level,
+    tool: Tool,
+    cursor: Position,
+    // First end of a portal pair, waiting for its partner
+    pending_portal: Option<Position>,
+    // Key of the message next to the tool
+    status: Option<&'static str>,
+    dirty: bool,
+}
+impl Editor {
+    fn new(level: Level) -> Editor {
+        Editor {
+            level,
+            tool: Tool::Wall,
+            cursor: Position { x: 0, y: 0 },
+            pending_portal: None,
+            status: None,
+            dirty: true,
+        }
+    }
+
+    fn in_bounds(&self, pos: Position) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.level.width && pos.y < self.level.height
+    }
+
+    fn is_painted(&self, pos: Position) -> bool {
+        match self.tool {
+            Tool::Wall => self.level.walls.contains(&pos),
+            Tool::Food => self.level.food.contains(&pos),
+            Tool::Spawn => self.level.spawn == Some(pos),
+            Tool::Portal => {
+                self.pending_portal == Some(pos)
+                    || self
+                        .level
+                        .portals
+                        .iter()
+                        .any(|(a, b)| *a == pos || *b == pos)
+            }
+        }
+    }
+
+    // A cell holds one thing at most, so painting replaces what was there
+    fn erase(&mut self, pos: Position) {
+        let level = &mut self.level;
+        level.walls.retain(|p| *p != pos);
+        level.food.retain(|p| *p != pos);
+        level.keys.retain(|p| *p != pos);
+        level.portals.retain(|(a, b)| *a != pos && *b != pos);
+        if level.spawn == Some(pos) {
+            level.spawn = None;
+        }
+        if self.pending_portal == Some(pos) {
+            self.pending_portal = None;
+        }
+        self.dirty = true;
+    }
+
+    fn paint(&mut self, pos: Position) {
+        if !self.in_bounds(pos) || self.is_painted(pos) {
+            return;
+        }
+        self.erase(pos);
+        match self.tool {
+            Tool::Wall => self.level.walls.push(pos),
+            Tool::Food => self.level.food.push(pos),
+            Tool::Spawn => self.level.spawn = Some(pos),
+            Tool::Portal => match self.pending_portal.take() {
+                Some(entrance) => {
+                    self.level.portals.push((entrance, pos));
+                    self.status = None;
+                }
+                None => {
+                    self.pending_portal = Some(pos);
+                    self.status = Some("editor.pick_portal_exit");
+                }
+            },
+        }
+    }
+
+    // Anything left outside the new size is dropped
+    fn resize(&mut self, width: i32, height: i32) {
+        let width = width.clamp(MIN_WIDTH, MAX_WIDTH);
+        let height = height.clamp(MIN_HEIGHT, MAX_HEIGHT);
+        let inside = |pos: &Position| pos.x < width && pos.y < height;
+        let level = &mut self.level;
+        level.width = width;
+        level.height = height;
+        level.walls.retain(inside);
+        level.food.retain(inside);
+        level.keys.retain(inside);
+        level.portals.retain(|(a, b)| inside(a) && inside(b));
+        level
+            .hazards
+            .retain(|path| path.waypoints.iter().all(inside));
+        level.spawn = level.spawn.filter(inside);
+        self.pending_portal = self.pending_portal.filter(inside);
+        self.cursor.x = self.cursor.x.min(width - 1);
+        self.cursor.y = self.cursor.y.min(height - 1);
+        self.dirty = true;
+    }
+}
+
+// Cells are as big as fits in the game size, with the arena centered
+struct Grid {
+    cell: f32,
+    top_left: Vec2,
+}
+impl Grid {
+    fn new(level: &Level, game_size: &GameSize) -> Grid {
+        let cell = (game_size.width / level.width as f32)
+            .min((game_size.height - 2.0 * GRID_MARGIN) / level.height as f32);
+        Grid {
+            cell,
+            top_left: Vec2::new(
+                -cell * level.width as f32 / 2.0,
+                cell * level.height as f32 / 2.0,
+            ),
+        }
+    }
+
+    fn to_world(&self, pos: Position) -> Vec2 {
+        Vec2::new(
+            self.top_left.x + (pos.x as f32 + 0.5) * self.cell,
+            self.top_left.y - (pos.y as f32 + 0.5) * self.cell,
+        )
+    }
+
+    fn to_cell(&self, world: Vec2) -> Position {
+        Position {
+            x: ((world.x - self.top_left.x) / self.cell).floor() as i32,
+            y: ((self.top_left.y - world.y) / self.cell).floor() as i32,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct EditorTile;
+
+#[derive(Component, Debug)]
+struct EditorCursor;
+
+#[derive(Component, Debug)]
+struct EditorHud;
+
+#[derive(Component, Debug)]
+struct EditorStatus;
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum EditorStages {
+    Input,
+    Draw,
+}
+
+pub struct EditorStatePlugin;
+impl Plugin for EditorStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Editor::new(storage::load::<Level>(CUSTOM_LEVEL_FILE)))
+            .add_system_set(SystemSet::on_enter(AppState::EditorState).with_system(spawn_editor))
+            .add_system_set(
+                SystemSet::on_update(AppState::EditorState)
+                    .with_system(editor_keyboard.label(EditorStages::Input))
+                    .with_system(editor_mouse.label(EditorStages::Input))
+                    .with_system(
+                        draw_editor
+                            .label(EditorStages::Draw)
+                            .after(EditorStages::Input),
+                    )
+                    .with_system(draw_cursor.after(EditorStages::Draw)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::EditorState).with_system(destroy_editor));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_editor(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    config: Res<Config>,
+    game_size: Res<GameSize>,
+    mut editor: ResMut<Editor>,
+    mut key_input: ResMut<Input<KeyCode>>,
+    theme: Res<Theme>,
+    locale: Res<Locale>,
+) {
+    // The key that opened the editor must not also paint or leave
+    key_input.reset(KeyCode::Space);
+    key_input.reset(KeyCode::Escape);
+    editor.dirty = true;
+
+    let font = game_assets.font.clone();
+    let text_style = TextStyle {
+        font,
+        font_size: 20.0 * config.text_scale,
+        color: theme.hud,
+    };
+    let text_alignment = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+    let hud_y = game_size.height / 2.0 - GRID_MARGIN / 2.0;
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section("", text_style.clone(), text_alignment),
+            transform: Transform::from_xyz(0.0, hud_y, 20.0),
+            ..Default::default()
+        })
+        .insert(EditorHud)
+        .insert(EditorStatus)
+        .insert(Themed(Palette::Hud));
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(locale.get("editor.help"), text_style, text_alignment),
+            transform: Transform::from_xyz(0.0, -hud_y, 20.0),
+            ..Default::default()
+        })
+        .insert(EditorHud)
+        .insert(Themed(Palette::Hud));
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: theme.editor_cursor,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(EditorCursor)
+        .insert(Themed(Palette::EditorCursor));
+}
+
+fn editor_keyboard(
+    key_input: Res<Input<KeyCode>>,
+    mut editor: ResMut<Editor>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut game_mode: ResMut<GameMode>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    if key_input.just_pressed(KeyCode::Escape) {
+        app_state.set(AppState::MainMenuState).unwrap();
+        return;
+    }
+    if key_input.just_pressed(KeyCode::P) {
+        current_level.0 = editor.level.clone();
+        *game_mode = GameMode::Playtest;
+        app_state.set(AppState::PlayState).unwrap();
+        return;
+    }
+    if key_input.just_pressed(KeyCode::S) {
+        storage::save(CUSTOM_LEVEL_FILE, &editor.level);
+        editor.status = Some("editor.saved");
+        editor.dirty = true;
+    }
+
+    // Tools
+    let tool = if key_input.just_pressed(KeyCode::Key1) {
+        Some(Tool::Wall)
+    } else if key_input.just_pressed(KeyCode::Key2) {
+        Some(Tool::Portal)
+    } else if key_input.just_pressed(KeyCode::Key3) {
+        Some(Tool::Spawn)
+    } else if key_input.just_pressed(KeyCode::Key4) {
+        Some(Tool::Food)
+    } else if key_input.just_pressed(KeyCode::Tab) {
+        Some(editor.tool.next())
+    } else {
+        None
+    };
+    if let Some(tool) = tool {
+        editor.tool = tool;
+        editor.pending_portal = None;
+        editor.status = None;
+        editor.dirty = true;
+    }
+
+    // Arena size
+    let (width, height) = (editor.level.width, editor.level.height);
+    if key_input.just_pressed(KeyCode::LBracket) {
+        editor.resize(width - 1, height);
+    }
+    if key_input.just_pressed(KeyCode::RBracket) {
+        editor.resize(width + 1, height);
+    }
+    if key_input.just_pressed(KeyCode::Minus) {
+        editor.resize(width, height - 1);
+    }
+    if key_input.just_pressed(KeyCode::Equals) {
+        editor.resize(width, height + 1);
+    }
+
+    // Cursor
+    let mut cursor = editor.cursor;
+    if key_input.just_pressed(KeyCode::Left) {
+        cursor.x -= 1;
+    }
+    if key_input.just_pressed(KeyCode::Right) {
+        cursor.x += 1;
+    }
+    if key_input.just_pressed(KeyCode::Up) {
+        cursor.y -= 1;
+    }
+    if key_input.just_pressed(KeyCode::Down) {
+        cursor.y += 1;
+    }
+    if editor.in_bounds(cursor) {
+        editor.cursor = cursor;
+    }
+    if key_input.just_pressed(KeyCode::Space) {
+        let cursor = editor.cursor;
+        editor.paint(cursor);
+    }
+    if key_input.just_pressed(KeyCode::Delete) || key_input.just_pressed(KeyCode::Back) {
+        let cursor = editor.cursor;
+        editor.erase(cursor);
+    }
+}
+
+fn editor_mouse(
+    windows: Res<Windows>,
+    mouse_input: Res<Input<MouseButton>>,
+    game_size: Res<GameSize>,
+    mut cursor_reader: EventReader<CursorMoved>,
+    mut editor: ResMut<Editor>,
+) {
+    // A mouse resting over the grid leaves the cursor to the arrow keys
+    let moved = cursor_reader.iter().last().is_some();
+    let pressed = mouse_input.pressed(MouseButton::Left) || mouse_input.pressed(MouseButton::Right);
+    if !moved && !pressed {
+        return;
+    }
+    let win = match windows.get_primary() {
+        Some(win) => win,
+        None => return,
+    };
+    let cursor_position = match win.cursor_position() {
+        Some(pos) => pos,
+        None => return,
+    };
+    let world = game_size.window_to_world(win, cursor_position);
+    let cell = Grid::new(&editor.level, &game_size).to_cell(world);
+    if !editor.in_bounds(cell) {
+        return;
+    }
+    editor.cursor = cell;
+    // Walls and food can be dragged, the rest are placed once per click
+    let drag = matches!(editor.tool, Tool::Wall | Tool::Food);
+    if mouse_input.just_pressed(MouseButton::Left)
+        || (drag && mouse_input.pressed(MouseButton::Left))
+    {
+        editor.paint(cell);
+    }
+    if mouse_input.pressed(MouseButton::Right)
+        && (editor.is_painted(cell) || editor.level.keys.contains(&cell))
+    {
+        editor.erase(cell);
+    }
+}
+
+fn draw_editor(
+    mut commands: Commands,
+    game_size: Res<GameSize>,
+    mut editor: ResMut<Editor>,
+    theme: Res<Theme>,
+    locale: Res<Locale>,
+    tile_query: Query<Entity, With<EditorTile>>,
+    mut status_query: Query<&mut Text, With<EditorStatus>>,
+) {
+    if !editor.dirty {
+        return;
+    }
+    editor.dirty = false;
+
+    for ent in tile_query.iter() {
+        commands.entity(ent).despawn();
+    }
+    let level = &editor.level;
+    let grid = Grid::new(level, &game_size);
+    let mut spawn_tile = |pos: Position, palette: Palette, size: f32, z: f32| {
+        let world = grid.to_world(pos);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: theme.color(palette),
+                    custom_size: Some(Vec2::new(grid.cell * size, grid.cell * size)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(world.x, world.y, z),
+                ..Default::default()
+            })
+            .insert(EditorTile)
+            .insert(Themed(palette));
+    };
+
+    for pos in level.walls.iter() {
+        spawn_tile(*pos, Palette::Wall, 1.0, 5.0);
+    }
+    for (a, b) in level.portals.iter() {
+        spawn_tile(*a, Palette::Portal, 1.0, 5.0);
+        spawn_tile(*b, Palette::Portal, 1.0, 5.0);
+    }
+    if let Some(pos) = editor.pending_portal {
+        spawn_tile(pos, Palette::PendingPortal, 1.0, 5.0);
+    }
+    for pos in level.keys.iter() {
+        spawn_tile(*pos, Palette::Key, 0.5, 5.0);
+    }
+    for pos in level.food.iter() {
+        spawn_tile(*pos, Palette::Food, 0.75, 10.0);
+    }
+    for path in level.hazards.iter() {
+        if let Some(pos) = path.waypoints.first() {
+            spawn_tile(*pos, Palette::Hazard, 0.85, 10.0);
+        }
+    }
+    spawn_tile(level.spawn_point(), Palette::Head, 0.95, 10.0);
+
+    // Arena background
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: theme.editor_background,
+                custom_size: Some(Vec2::new(
+                    grid.cell * level.width as f32,
+                    grid.cell * level.height as f32,
+                )),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..Default::default()
+        })
+        .insert(EditorTile)
+        .insert(Themed(Palette::EditorBackground));
+
+    if let Ok(mut text) = status_query.get_single_mut() {
+        text.sections[0].value = format!(
+            "{}  {}x{}  {}",
+            locale.get(editor.tool.key()),
+            level.width,
+            level.height,
+            editor.status.map_or("", |key| locale.get(key))
+        );
+    }
+}
+
+fn draw_cursor(
+    game_size: Res<GameSize>,
+    editor: Res<Editor>,
+    mut query: Query<(&mut Transform, &mut Sprite), With<EditorCursor>>,
+) {
+    let grid = Grid::new(&editor.level, &game_size);
+    let world = grid.to_world(editor.cursor);
+    for (mut transform, mut sprite) in query.iter_mut() {
+        transform.translation = Vec3::new(world.x, world.y, 15.0);
+        sprite.custom_size = Some(Vec2::new(grid.cell, grid.cell));
+    }
+}
+
+fn destroy_editor(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<EditorTile>, With<EditorCursor>, With<EditorHud>)>>,
+) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+}
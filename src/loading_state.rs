@@ -0,0 +1,201 @@
+use super::AppState;
+use crate::cli::Launch;
+use crate::config::Config;
+use crate::locale::{Locale, Strings, LANGUAGES};
+use crate::menu_state::tapped;
+use crate::play_state::skin::SKINS;
+use crate::theme::{Palette, Theme, Themed, THEMES};
+use bevy::{asset::LoadState, prelude::*};
+
+const FONT: &str = "Open_Sans/OpenSans-ExtraBold.ttf";
+const BAR_WIDTH: f32 = 400.0;
+const BAR_HEIGHT: f32 = 20.0;
+
+// Everything loaded from the assets folder, shared by every screen. Levels,
+// achievements and sounds are built into the game and need no loading.
+pub struct GameAssets {
+    pub font: Handle<Font>,
+    pub themes: Vec<Handle<Theme>>,
+    pub languages: Vec<Handle<Strings>>,
+    pub skins: Vec<Handle<Image>>,
+}
+
+// Every handle of `GameAssets` with its path, to tell which one is missing
+struct PendingAssets(Vec<(String, HandleUntyped)>);
+
+#[derive(Component, Debug)]
+struct LoadingScreen;
+
+#[derive(Component, Debug)]
+struct LoadingBar;
+
+#[derive(Component, Debug)]
+struct LoadingStatus;
+
+pub struct LoadingStatePlugin;
+impl Plugin for LoadingStatePlugin {
+    fn build(&self, app: &mut App) {
+        // Before startup so the other plugins can set up with the handles
+        app.add_startup_system_to_stage(StartupStage::PreStartup, load_assets)
+            .add_system_set(SystemSet::on_enter(AppState::LoadingState).with_system(spawn_loading))
+            .add_system_set(
+                SystemSet::on_update(AppState::LoadingState).with_system(update_loading),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::LoadingState).with_system(destroy_loading),
+            );
+    }
+}
+
+fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if let Err(e) = asset_server.watch_for_changes() {
+        warn!("Theme and language files won't reload on change: {:?}", e);
+    }
+    let mut pending = Vec::new();
+    let mut load = |path: String| {
+        let handle = asset_server.load_untyped(path.as_str());
+        pending.push((path, handle.clone()));
+        handle
+    };
+    let game_assets = GameAssets {
+        font: load(FONT.to_string()).typed(),
+        themes: THEMES
+            .iter()
+            .map(|name| load(format!("themes/{}.theme.ron", name)).typed())
+            .collect(),
+        languages: LANGUAGES
+            .iter()
+            .map(|name| load(format!("lang/{}.lang.ron", name)).typed())
+            .collect(),
+        skins: SKINS
+            .iter()
+            .map(|name| load(format!("skins/{}.png", name)).typed())
+            .collect(),
+    };
+    commands.insert_resource(game_assets);
+    commands.insert_resource(PendingAssets(pending));
+}
+
+// The bar needs no font, so it shows even before the font is in
+fn spawn_loading(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    config: Res<Config>,
+    theme: Res<Theme>,
+) {
+    let mut spawn_bar = |palette: Palette, width: f32, z: f32| {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: theme.color(palette),
+                    custom_size: Some(Vec2::new(width, BAR_HEIGHT)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, z),
+                ..Default::default()
+            })
+            .insert(Themed(palette))
+            .insert(LoadingScreen)
+            .id()
+    };
+    spawn_bar(Palette::Locked, BAR_WIDTH, 10.0);
+    let bar = spawn_bar(Palette::Selected, 0.0, 11.0);
+    commands.entity(bar).insert(LoadingBar);
+
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 25.0 * config.text_scale,
+                    color: theme.controls,
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Top,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            transform: Transform::from_xyz(0.0, -40.0, 10.0),
+            ..Default::default()
+        })
+        .insert(Themed(Palette::Controls))
+        .insert(LoadingScreen)
+        .insert(LoadingStatus);
+}
+
+// Moves on by itself once everything is in. Missing files are listed and can
+// be skipped, the bundled defaults stand in for a theme or a language.
+#[allow(clippy::too_many_arguments)]
+fn update_loading(
+    asset_server: Res<AssetServer>,
+    pending: Res<PendingAssets>,
+    launch: Res<Launch>,
+    locale: Res<Locale>,
+    mut key_input: ResMut<Input<KeyCode>>,
+    mut mouse_input: ResMut<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut app_state: ResMut<State<AppState>>,
+    mut reported: Local<bool>,
+    mut bar_query: Query<(&mut Sprite, &mut Transform), With<LoadingBar>>,
+    mut status_query: Query<&mut Text, With<LoadingStatus>>,
+) {
+    let mut done = 0;
+    let mut missing = Vec::new();
+    for (path, handle) in pending.0.iter() {
+        match asset_server.get_load_state(handle) {
+            LoadState::Loaded => done += 1,
+            LoadState::Failed => {
+                done += 1;
+                missing.push(path.as_str());
+            }
+            LoadState::NotLoaded | LoadState::Loading => (),
+        }
+    }
+
+    let width = BAR_WIDTH * done as f32 / pending.0.len().max(1) as f32;
+    for (mut sprite, mut transform) in bar_query.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(width, BAR_HEIGHT));
+        transform.translation.x = (width - BAR_WIDTH) / 2.0;
+    }
+
+    if done < pending.0.len() {
+        return;
+    }
+    let next_state = if launch.skip_menu {
+        AppState::PlayState
+    } else {
+        AppState::MainMenuState
+    };
+    if missing.is_empty() {
+        app_state.set(next_state).unwrap();
+        return;
+    }
+    if !*reported {
+        *reported = true;
+        for path in missing.iter() {
+            error!("Missing asset: assets/{}", path);
+        }
+        let mut lines: Vec<String> = missing
+            .iter()
+            .map(|path| locale.fill("loading.missing", path))
+            .collect();
+        lines.push(String::new());
+        lines.push(locale.get("loading.continue").to_string());
+        for mut text in status_query.iter_mut() {
+            text.sections[0].value = lines.join("\n");
+        }
+    }
+    if key_input.just_pressed(KeyCode::Space) || tapped(&mouse_input, &touches) {
+        // Skipping must not also pick something in the menu
+        key_input.reset(KeyCode::Space);
+        mouse_input.reset(MouseButton::Left);
+        app_state.set(next_state).unwrap();
+    }
+}
+
+fn destroy_loading(mut commands: Commands, query: Query<Entity, With<LoadingScreen>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+}
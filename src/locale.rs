@@ -0,0 +1,135 @@
+use crate::loading_state::GameAssets;
+use crate::storage;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Bundled string tables, loaded through the asset server like the themes
+pub const LANGUAGES: [&str; 3] = ["en", "de", "es"];
+const ENGLISH: &str = include_str!("../assets/lang/en.lang.ron");
+pub const LANGUAGE_FILE: &str = "language.ron";
+
+// Every text shown to the player by key
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "8c2d4e6f-1a3b-4c5d-9e7f-0a1b2c3d4e5f"]
+pub struct Strings(HashMap<String, String>);
+
+// The strings of the picked language. English is always kept, a key missing
+// from the picked table falls back to it and then to the key itself.
+#[derive(Debug)]
+pub struct Locale {
+    english: Strings,
+    picked: Strings,
+}
+impl Default for Locale {
+    fn default() -> Self {
+        let english =
+            Strings(ron::from_str(ENGLISH).expect("Bundled English string table is malformed"));
+        Locale {
+            picked: english.clone(),
+            english,
+        }
+    }
+}
+impl Locale {
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.get_or(key, key)
+    }
+
+    // For texts that come with their own English, like the achievements file
+    pub fn get_or<'a>(&'a self, key: &str, fallback: &'a str) -> &'a str {
+        self.picked
+            .0
+            .get(key)
+            .or_else(|| self.english.0.get(key))
+            .map_or(fallback, |value| value.as_str())
+    }
+
+    // Puts a value where the text has `{}`
+    pub fn fill(&self, key: &str, value: impl ToString) -> String {
+        self.get(key).replacen("{}", &value.to_string(), 1)
+    }
+}
+
+// Which language is picked, saved between sessions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LanguageChoice {
+    pub name: String,
+}
+impl Default for LanguageChoice {
+    fn default() -> Self {
+        LanguageChoice {
+            name: LANGUAGES[0].to_string(),
+        }
+    }
+}
+impl LanguageChoice {
+    fn index(&self) -> usize {
+        LANGUAGES
+            .iter()
+            .position(|name| *name == self.name)
+            .unwrap_or(0)
+    }
+
+    pub fn next(&mut self) {
+        self.name = LANGUAGES[(self.index() + 1) % LANGUAGES.len()].to_string();
+    }
+}
+
+#[derive(Default)]
+struct StringsLoader;
+impl AssetLoader for StringsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let strings = Strings(ron::de::from_bytes(bytes)?);
+            load_context.set_default_asset(LoadedAsset::new(strings));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang.ron"]
+    }
+}
+
+pub struct LocalePlugin;
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Strings>()
+            .init_asset_loader::<StringsLoader>()
+            .init_resource::<Locale>()
+            .insert_resource(storage::load::<LanguageChoice>(LANGUAGE_FILE))
+            .add_system(apply_language);
+    }
+}
+
+// Copies the picked table into the resource when it is picked, loaded or edited.
+// Screens read it when they are spawned, menus redraw when their labels change.
+fn apply_language(
+    mut events: EventReader<AssetEvent<Strings>>,
+    choice: Res<LanguageChoice>,
+    game_assets: Res<GameAssets>,
+    tables: Res<Assets<Strings>>,
+    mut locale: ResMut<Locale>,
+) {
+    let handle = &game_assets.languages[choice.index()];
+    let reloaded = events.iter().any(|event| match event {
+        AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h } => h == handle,
+        AssetEvent::Removed { .. } => false,
+    });
+    if !reloaded && !choice.is_changed() {
+        return;
+    }
+    if let Some(picked) = tables.get(handle) {
+        locale.picked = picked.clone();
+    }
+}
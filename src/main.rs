@@ -1,78 +1,203 @@
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::window::{WindowMode, WindowResized};
+use cli::CliPlugin;
+use config::ConfigPlugin;
+use editor_state::EditorStatePlugin;
+use loading_state::LoadingStatePlugin;
+use locale::LocalePlugin;
 use menu_state::MenuStatePlugin;
 use play_state::PlayStatePlugin;
+use sound::SoundPlugin;
+use theme::{Palette, Theme, ThemePlugin, Themed};
 
+mod cli;
+mod config;
+mod editor_state;
+mod loading_state;
+mod locale;
 mod menu_state;
 mod play_state;
+mod sound;
+mod storage;
+mod synth;
+mod theme;
+mod tournament;
 
-const WIN_WIDTH: f32 = 800.0;
-const WIN_HEIGHT: f32 = 600.0;
+// Everything is laid out for this, the window starts at the size in `Config`
+const GAME_WIDTH: f32 = 800.0;
+const GAME_HEIGHT: f32 = 600.0;
 
+// The game area in world units. The camera zooms so it always fits the window,
+// whatever is left over is covered by the letterbox bars.
 #[derive(Debug)]
 struct GameSize {
     width: f32,
     height: f32,
 }
+impl GameSize {
+    // World units per window pixel
+    fn window_scale(&self, win_width: f32, win_height: f32) -> f32 {
+        (self.width / win_width).max(self.height / win_height)
+    }
+
+    // Window coordinates start at the bottom left, an unmoved camera shows the
+    // game size centered
+    fn window_to_world(&self, win: &Window, position: Vec2) -> Vec2 {
+        let scale = self.window_scale(win.width(), win.height());
+        (position - Vec2::new(win.width(), win.height()) / 2.0) * scale
+    }
+}
+
+// Points from the game area to the side the bar covers
+#[derive(Component, Debug)]
+struct Letterbox(Vec2);
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 enum AppState {
+    LoadingState,
     MainMenuState,
+    LevelSelectState,
+    EditorState,
     PlayState,
+    HighScoresState,
+    AchievementsState,
+    StatsState,
 }
 
 fn main() {
     let mut app = App::new();
 
-    app.insert_resource(WindowDescriptor {
-        title: "Snaky".to_string(),
-        width: WIN_WIDTH,
-        height: WIN_HEIGHT,
-        ..Default::default()
-    })
-    .insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1)))
-    .insert_resource(GameSize {
-        width: WIN_WIDTH,
-        height: WIN_HEIGHT,
-    });
+    app.add_plugin(CliPlugin)
+        .add_plugin(ConfigPlugin)
+        .insert_resource(GameSize {
+            width: GAME_WIDTH,
+            height: GAME_HEIGHT,
+        });
 
-    app.add_state(AppState::MainMenuState);
+    app.add_state(AppState::LoadingState);
 
     app.add_plugin(LogDiagnosticsPlugin::default())
         .add_plugin(FrameTimeDiagnosticsPlugin::default());
 
-    app.add_plugins(DefaultPlugins)
-        .add_plugin(PlayStatePlugin)
-        .add_plugin(MenuStatePlugin);
+    // Bevy's audio panics without an output device, `sound` opens its own
+    app.add_plugins_with(DefaultPlugins, |group| {
+        group.disable::<bevy::audio::AudioPlugin>()
+    })
+    .add_plugin(LoadingStatePlugin)
+    .add_plugin(ThemePlugin)
+    .add_plugin(LocalePlugin)
+    .add_plugin(SoundPlugin)
+    .add_plugin(PlayStatePlugin)
+    .add_plugin(MenuStatePlugin)
+    .add_plugin(EditorStatePlugin);
 
-    app.add_startup_system(setup_camera);
+    app.add_startup_system(setup_camera)
+        .add_system(fit_to_window)
+        .add_system(toggle_fullscreen);
 
     app.run();
 }
 
-fn setup_camera(mut commands: Commands, win_res: Res<Windows>) {
+fn setup_camera(
+    mut commands: Commands,
+    win_res: Res<Windows>,
+    game_size: Res<GameSize>,
+    theme: Res<Theme>,
+) {
     // Get primary window
     let win = win_res.get_primary().unwrap();
     // Create camera
     let mut new_camera = OrthographicCameraBundle::new_2d();
     // Set camera variables
-    new_camera.orthographic_projection.scaling_mode =
-        bevy::render::camera::ScalingMode::FixedVertical;
-    new_camera.orthographic_projection.scale = win.height() / 2.0;
+    let (scaling_mode, scale, bar_size) = fit(win.width(), win.height(), &game_size);
+    new_camera.orthographic_projection.scaling_mode = scaling_mode;
+    new_camera.orthographic_projection.scale = scale;
+    // Letterboxing sprites move and zoom with the camera, placed in front of
+    // everything else
+    let z = 99.0 - new_camera.transform.translation.z;
     // Spawn new camera
-    commands.spawn_bundle(new_camera);
-    // Spawn letterboxing sprites
-    let mut spawn_letterboxing = |x_mul: f32| {
-        commands.spawn_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb(0.0, 0.0, 0.0),
-                custom_size: Some(Vec2::new(win.width(), win.height())),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(win.width() * x_mul, 0.0, 99.0),
-            ..Default::default()
-        });
+    commands.spawn_bundle(new_camera).with_children(|parent| {
+        // Spawn letterboxing sprites
+        for side in [Vec2::X, -Vec2::X, Vec2::Y, -Vec2::Y] {
+            parent
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: theme.letterbox,
+                        custom_size: Some(Vec2::splat(bar_size)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(
+                        letterbox_position(side, bar_size, &game_size).extend(z),
+                    ),
+                    ..Default::default()
+                })
+                .insert(Letterbox(side))
+                .insert(Themed(Palette::Letterbox));
+        }
+    });
+}
+
+// How the camera has to zoom to show all of the game area in a window of this
+// size, and how big the letterbox bars have to be to cover the rest of it
+fn fit(win_width: f32, win_height: f32, game_size: &GameSize) -> (ScalingMode, f32, f32) {
+    let scale = game_size.window_scale(win_width, win_height);
+    let bar_size = win_width.max(win_height) * scale;
+    if win_width / win_height >= game_size.width / game_size.height {
+        (ScalingMode::FixedVertical, game_size.height / 2.0, bar_size)
+    } else {
+        (
+            ScalingMode::FixedHorizontal,
+            game_size.width / 2.0,
+            bar_size,
+        )
+    }
+}
+
+// Bars sit right against the edge of the game area
+fn letterbox_position(side: Vec2, bar_size: f32, game_size: &GameSize) -> Vec2 {
+    side * (Vec2::new(game_size.width, game_size.height) + Vec2::splat(bar_size)) / 2.0
+}
+
+// Menus, text and the arena are all placed in game units, so zooming the camera
+// rescales everything at once
+fn fit_to_window(
+    mut resize_reader: EventReader<WindowResized>,
+    game_size: Res<GameSize>,
+    mut camera_query: Query<&mut OrthographicProjection>,
+    mut letterbox_query: Query<(&Letterbox, &mut Sprite, &mut Transform)>,
+) {
+    let resized = match resize_reader
+        .iter()
+        .filter(|event| event.id.is_primary())
+        .last()
+    {
+        Some(resized) => resized,
+        None => return,
     };
-    spawn_letterboxing(1.0);
-    spawn_letterboxing(-1.0);
+    let (scaling_mode, scale, bar_size) = fit(resized.width, resized.height, &game_size);
+    for mut projection in camera_query.iter_mut() {
+        projection.scaling_mode = scaling_mode;
+        projection.scale = scale;
+    }
+    for (letterbox, mut sprite, mut transform) in letterbox_query.iter_mut() {
+        sprite.custom_size = Some(Vec2::splat(bar_size));
+        let position = letterbox_position(letterbox.0, bar_size, &game_size);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+fn toggle_fullscreen(key_input: Res<Input<KeyCode>>, mut windows: ResMut<Windows>) {
+    if !key_input.just_pressed(KeyCode::F11) {
+        return;
+    }
+    if let Some(win) = windows.get_primary_mut() {
+        if win.mode() == WindowMode::Windowed {
+            win.set_mode(WindowMode::BorderlessFullscreen);
+        } else {
+            win.set_mode(WindowMode::Windowed);
+        }
+    }
 }
@@ -1,19 +1,30 @@
 use super::AppState;
+use awards::AwardsPlugin;
 use bevy::{ecs::schedule::ShouldRun, prelude::*};
-use controls::ControlPlugin;
-use options::OptionPlugin;
-use title::TitlePlugin;
+use high_scores::HighScoresPlugin;
+use level_select::LevelSelectPlugin;
+use main_menu::MainMenuPlugin;
+use menu::MenuPlugin;
+use stats::StatsScreenPlugin;
 
-mod controls;
-mod options;
-mod title;
+mod awards;
+mod high_scores;
+mod level_select;
+mod main_menu;
+mod menu;
+mod stats;
+
+pub use menu::tapped;
 
 pub struct MenuStatePlugin;
 impl Plugin for MenuStatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(TitlePlugin)
-            .add_plugin(ControlPlugin)
-            .add_plugin(OptionPlugin);
+        app.add_plugin(MenuPlugin)
+            .add_plugin(MainMenuPlugin)
+            .add_plugin(LevelSelectPlugin)
+            .add_plugin(HighScoresPlugin)
+            .add_plugin(AwardsPlugin)
+            .add_plugin(StatsScreenPlugin);
     }
 }
 
@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+
+use super::main_menu::go_to;
+use super::menu::{destroy_menu, Menu, MenuItem, MenuStack};
+use super::AppState;
+use crate::locale::Locale;
+use crate::play_state::achievements::{Achievements, UnlockedAchievements};
+use crate::theme::Palette;
+
+pub struct AwardsPlugin;
+impl Plugin for AwardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::AchievementsState).with_system(spawn_awards),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::AchievementsState).with_system(destroy_menu));
+    }
+}
+
+fn spawn_awards(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    mut key_input: ResMut<Input<KeyCode>>,
+    achievements: Res<Achievements>,
+    unlocked: Res<UnlockedAchievements>,
+) {
+    // The key that opened this screen must not also close it
+    key_input.reset(KeyCode::Space);
+
+    // Every award is its name and then what it asks for
+    let mut items = Vec::new();
+    for achievement in achievements.0.iter() {
+        let palette = if unlocked.ids.contains(&achievement.id) {
+            Palette::Highlight
+        } else {
+            Palette::Locked
+        };
+        let name = achievement.name(&locale).to_string();
+        let description = achievement.description(&locale).to_string();
+        items.push(MenuItem::line(move |_| (name.clone(), palette)));
+        items.push(MenuItem::line(move |_| (description.clone(), palette)));
+    }
+    items.push(MenuItem::Back);
+    let menu = Menu {
+        title: "awards.title",
+        items,
+        footer: vec!["settings.footer.back"],
+    };
+    commands.insert_resource(
+        MenuStack::new(menu).leaving(|world| go_to(world, AppState::MainMenuState)),
+    );
+}
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use super::main_menu::go_to;
+use super::menu::{destroy_menu, Menu, MenuItem, MenuStack};
+use super::AppState;
+use crate::locale::Locale;
+use crate::play_state::time_attack::{LastTimeAttack, TimeAttackScores};
+use crate::theme::Palette;
+
+pub struct HighScoresPlugin;
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::HighScoresState).with_system(spawn_high_scores),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::HighScoresState).with_system(destroy_menu));
+    }
+}
+
+fn spawn_high_scores(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    scores: Res<TimeAttackScores>,
+    last_run: Res<LastTimeAttack>,
+) {
+    // The run that just ended is highlighted
+    let last_rank = last_run.0.as_ref().and_then(|run| run.rank);
+    let mut items = Vec::new();
+    for (rank, score) in scores.scores.iter().enumerate() {
+        let line = if last_rank == Some(rank) {
+            (format!("- {}. {} -", rank + 1, score), Palette::Selected)
+        } else {
+            (format!("{}. {}", rank + 1, score), Palette::Item)
+        };
+        items.push(MenuItem::line(move |_| line.clone()));
+    }
+    if let Some(run) = last_run.0.as_ref().filter(|run| run.rank.is_none()) {
+        let line = locale.fill("high_scores.your_score", run.score);
+        items.push(MenuItem::line(move |_| (line.clone(), Palette::Selected)));
+    }
+    items.push(MenuItem::Back);
+    let menu = Menu {
+        title: "high_scores.title",
+        items,
+        footer: vec!["settings.footer.back"],
+    };
+    commands.insert_resource(
+        MenuStack::new(menu).leaving(|world| go_to(world, AppState::MainMenuState)),
+    );
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use super::main_menu::{go_to, play};
+use super::menu::{destroy_menu, resource, Menu, MenuItem, MenuStack};
+use super::AppState;
+use crate::locale::Locale;
+use crate::play_state::{
+    campaign::{campaign_level, CampaignProgress, CAMPAIGN_LENGTH},
+    GameMode,
+};
+use crate::theme::Palette;
+
+pub struct LevelSelectPlugin;
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::LevelSelectState).with_system(spawn_level_select),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::LevelSelectState).with_system(destroy_menu));
+    }
+}
+
+// Locked levels are named LOCKED and can't be played
+fn level_item(index: usize, name: String) -> MenuItem {
+    MenuItem::text(
+        move |world| {
+            if resource::<CampaignProgress>(world).is_unlocked(index) {
+                (name.clone(), Palette::Item)
+            } else {
+                let locked = resource::<Locale>(world).get("levels.locked");
+                (locked.to_string(), Palette::Locked)
+            }
+        },
+        move |world| {
+            if resource::<CampaignProgress>(world).is_unlocked(index) {
+                play(world, GameMode::Campaign(index), campaign_level(index));
+            }
+        },
+    )
+}
+
+fn spawn_level_select(
+    mut commands: Commands,
+    mut key_input: ResMut<Input<KeyCode>>,
+    game_mode: Res<GameMode>,
+    progress: Res<CampaignProgress>,
+) {
+    // The key that opened this screen must not also pick a level or leave
+    key_input.reset(KeyCode::Space);
+    key_input.reset(KeyCode::Escape);
+
+    // Names are read once here, the bundled levels don't change
+    let mut items: Vec<MenuItem> = (0..CAMPAIGN_LENGTH)
+        .map(|index| level_item(index, campaign_level(index).name))
+        .collect();
+    items.push(MenuItem::Back);
+    let menu = Menu {
+        title: "levels.title",
+        items,
+        footer: vec!["menu.footer.move", "settings.footer.back"],
+    };
+
+    // Start on the level the campaign is at, that is the next one after clearing a level
+    let selected = match *game_mode {
+        GameMode::Campaign(index) if progress.is_unlocked(index) => index,
+        _ => progress.cleared.min(CAMPAIGN_LENGTH - 1),
+    };
+    commands.insert_resource(
+        MenuStack::new(menu)
+            .starting_at(selected)
+            .leaving(|world| go_to(world, AppState::MainMenuState)),
+    );
+}
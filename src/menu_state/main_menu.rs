@@ -0,0 +1,182 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use super::menu::{destroy_menu, resource, resource_mut, Menu, MenuItem, MenuStack};
+use super::AppState;
+use crate::config::Config;
+use crate::locale::{LanguageChoice, Locale, LANGUAGE_FILE};
+use crate::play_state::{
+    camera::{CameraSettings, CAMERA_FILE},
+    level::{CurrentLevel, Level},
+    skin::{SkinChoice, SKIN_FILE},
+    survival::survival_level,
+    time_attack::LastTimeAttack,
+    touch::{TouchSettings, TOUCH_FILE},
+    GameMode, Rules, RULES_FILE,
+};
+use crate::sound::{SoundSettings, SOUND_FILE};
+use crate::storage;
+use crate::theme::{Theme, ThemeChoice, THEME_FILE};
+
+pub struct MainMenuPlugin;
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::MainMenuState).with_system(spawn_main_menu),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::MainMenuState).with_system(destroy_menu));
+    }
+}
+
+fn spawn_main_menu(mut commands: Commands) {
+    commands.insert_resource(MenuStack::new(main_menu()));
+}
+
+pub fn go_to(world: &mut World, state: AppState) {
+    resource_mut::<State<AppState>>(world).set(state).unwrap();
+}
+
+pub fn play(world: &mut World, game_mode: GameMode, level: Level) {
+    *resource_mut::<GameMode>(world) = game_mode;
+    resource_mut::<CurrentLevel>(world).0 = level;
+    go_to(world, AppState::PlayState);
+}
+
+fn main_menu() -> Menu {
+    Menu {
+        title: "menu.title",
+        items: vec![
+            MenuItem::action("menu.play", |world| {
+                let level = Level::classic(resource::<Config>(world));
+                play(world, GameMode::Classic, level)
+            }),
+            MenuItem::action("menu.timed", |world| {
+                let level = Level::classic(resource::<Config>(world));
+                play(world, GameMode::TimeAttack, level)
+            }),
+            MenuItem::action("menu.survival", |world| {
+                play(world, GameMode::Survival, survival_level())
+            }),
+            MenuItem::action("menu.levels", |world| {
+                go_to(world, AppState::LevelSelectState)
+            }),
+            MenuItem::action("menu.editor", |world| go_to(world, AppState::EditorState)),
+            MenuItem::action("menu.awards", |world| {
+                go_to(world, AppState::AchievementsState)
+            }),
+            MenuItem::action("menu.scores", |world| {
+                resource_mut::<LastTimeAttack>(world).0 = None;
+                go_to(world, AppState::HighScoresState)
+            }),
+            MenuItem::action("menu.stats", |world| go_to(world, AppState::StatsState)),
+            MenuItem::Submenu("menu.settings", settings_menu),
+            MenuItem::action("menu.quit", |world| {
+                resource_mut::<Events<AppExit>>(world).send(AppExit)
+            }),
+        ],
+        footer: vec!["menu.footer.move", "menu.footer.select"],
+    }
+}
+
+// Every setting is saved as soon as it changes
+fn settings_menu() -> Menu {
+    Menu {
+        title: "menu.settings",
+        items: vec![
+            MenuItem::toggle(
+                "settings.sound",
+                |world| !resource::<SoundSettings>(world).muted,
+                |world, on| {
+                    let mut sound = resource_mut::<SoundSettings>(world);
+                    sound.muted = !on;
+                    storage::save(SOUND_FILE, &*sound);
+                },
+            ),
+            MenuItem::slider(
+                "settings.volume",
+                |world| resource::<SoundSettings>(world).volume,
+                |world, volume| {
+                    let mut sound = resource_mut::<SoundSettings>(world);
+                    sound.volume = volume;
+                    storage::save(SOUND_FILE, &*sound);
+                },
+            ),
+            // Shows the name in the theme file, that changes once it is loaded
+            MenuItem::choice(
+                "settings.theme",
+                |world| resource::<Theme>(world).name.clone(),
+                |world| {
+                    let mut theme_choice = resource_mut::<ThemeChoice>(world);
+                    theme_choice.next();
+                    storage::save(THEME_FILE, &*theme_choice);
+                },
+            ),
+            MenuItem::choice(
+                "settings.skin",
+                |world| resource::<SkinChoice>(world).name.to_uppercase(),
+                |world| {
+                    let mut skin = resource_mut::<SkinChoice>(world);
+                    skin.next();
+                    storage::save(SKIN_FILE, &*skin);
+                },
+            ),
+            // Like the theme, named by its table once that is loaded
+            MenuItem::choice(
+                "settings.language",
+                |world| resource::<Locale>(world).get("language.name").to_string(),
+                |world| {
+                    let mut language = resource_mut::<LanguageChoice>(world);
+                    language.next();
+                    storage::save(LANGUAGE_FILE, &*language);
+                },
+            ),
+            MenuItem::toggle(
+                "settings.tail_cut",
+                |world| resource::<Rules>(world).tail_cut,
+                |world, on| {
+                    let mut rules = resource_mut::<Rules>(world);
+                    rules.tail_cut = on;
+                    storage::save(RULES_FILE, &*rules);
+                },
+            ),
+            MenuItem::toggle(
+                "settings.follow_camera",
+                |world| resource::<CameraSettings>(world).follow,
+                |world, on| {
+                    let mut camera = resource_mut::<CameraSettings>(world);
+                    camera.follow = on;
+                    storage::save(CAMERA_FILE, &*camera);
+                },
+            ),
+            MenuItem::choice(
+                "settings.zoom",
+                |world| format!("x{}", resource::<CameraSettings>(world).magnification()),
+                |world| {
+                    let mut camera = resource_mut::<CameraSettings>(world);
+                    camera.next_zoom();
+                    storage::save(CAMERA_FILE, &*camera);
+                },
+            ),
+            MenuItem::toggle(
+                "settings.minimap",
+                |world| resource::<CameraSettings>(world).minimap,
+                |world, on| {
+                    let mut camera = resource_mut::<CameraSettings>(world);
+                    camera.minimap = on;
+                    storage::save(CAMERA_FILE, &*camera);
+                },
+            ),
+            MenuItem::toggle(
+                "settings.dpad",
+                |world| resource::<TouchSettings>(world).dpad,
+                |world, on| {
+                    let mut touch = resource_mut::<TouchSettings>(world);
+                    touch.dpad = on;
+                    storage::save(TOUCH_FILE, &*touch);
+                },
+            ),
+            MenuItem::Back,
+        ],
+        footer: vec!["settings.footer.change", "settings.footer.back"],
+    }
+}
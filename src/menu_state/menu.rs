@@ -0,0 +1,455 @@
+use bevy::{prelude::*, text::Text2dSize, window::CursorMoved};
+
+use crate::config::Config;
+use crate::loading_state::GameAssets;
+use crate::locale::Locale;
+use crate::sound::{PlaySound, Sound};
+use crate::theme::{Palette, Theme, Themed};
+use crate::GameSize;
+
+const TITLE_Y: f32 = 205.0;
+// Items are spaced evenly around this height
+const ITEMS_Y: f32 = -25.0;
+const ITEM_SPACING: f32 = 34.0;
+// Long menus are squeezed to fit between the title and the footer
+const ITEMS_HEIGHT: f32 = 340.0;
+const FOOTER_Y: f32 = -240.0;
+const FOOTER_SPACING: f32 = 30.0;
+const SLIDER_STEP: f32 = 0.1;
+
+type Action = Box<dyn Fn(&mut World) + Send + Sync>;
+type Getter<T> = Box<dyn Fn(&World) -> T + Send + Sync>;
+type Setter<T> = Box<dyn Fn(&mut World, T) + Send + Sync>;
+
+// One line of a menu, names are keys into the string tables. The callbacks get
+// the whole world so an item can touch any resource it needs.
+pub enum MenuItem {
+    // Space runs it
+    Action(&'static str, Action),
+    // Space flips it, shown as ON or OFF
+    Toggle(&'static str, Getter<bool>, Setter<bool>),
+    // Left and Right move it between 0 and 1, shown in percent
+    Slider(&'static str, Getter<f32>, Setter<f32>),
+    // Space picks the next value, shown after the name
+    Choice(&'static str, Getter<String>, Action),
+    // Space opens the menu on top of this one
+    Submenu(&'static str, fn() -> Menu),
+    // Space goes back to the menu below, as does Escape anywhere
+    Back,
+    // Shown as it is in its own colour, for names that aren't in the string
+    // tables. Space runs it, without anything to run it is just a line of a
+    // list and can't be picked.
+    Text(Getter<(String, Palette)>, Option<Action>),
+}
+impl MenuItem {
+    pub fn action(name: &'static str, run: impl Fn(&mut World) + Send + Sync + 'static) -> Self {
+        MenuItem::Action(name, Box::new(run))
+    }
+
+    pub fn toggle(
+        name: &'static str,
+        get: impl Fn(&World) -> bool + Send + Sync + 'static,
+        set: impl Fn(&mut World, bool) + Send + Sync + 'static,
+    ) -> Self {
+        MenuItem::Toggle(name, Box::new(get), Box::new(set))
+    }
+
+    pub fn slider(
+        name: &'static str,
+        get: impl Fn(&World) -> f32 + Send + Sync + 'static,
+        set: impl Fn(&mut World, f32) + Send + Sync + 'static,
+    ) -> Self {
+        MenuItem::Slider(name, Box::new(get), Box::new(set))
+    }
+
+    pub fn choice(
+        name: &'static str,
+        get: impl Fn(&World) -> String + Send + Sync + 'static,
+        next: impl Fn(&mut World) + Send + Sync + 'static,
+    ) -> Self {
+        MenuItem::Choice(name, Box::new(get), Box::new(next))
+    }
+
+    pub fn text(
+        get: impl Fn(&World) -> (String, Palette) + Send + Sync + 'static,
+        run: impl Fn(&mut World) + Send + Sync + 'static,
+    ) -> Self {
+        MenuItem::Text(Box::new(get), Some(Box::new(run)))
+    }
+
+    pub fn line(get: impl Fn(&World) -> (String, Palette) + Send + Sync + 'static) -> Self {
+        MenuItem::Text(Box::new(get), None)
+    }
+
+    fn can_pick(&self) -> bool {
+        !matches!(self, MenuItem::Text(_, None))
+    }
+
+    fn label(&self, world: &World) -> (String, Palette) {
+        let locale = resource::<Locale>(world);
+        let label = match self {
+            MenuItem::Action(name, _) | MenuItem::Submenu(name, _) => locale.get(name).to_string(),
+            MenuItem::Toggle(name, get, _) => {
+                let value = if get(world) { "menu.on" } else { "menu.off" };
+                format!("{}: {}", locale.get(name), locale.get(value))
+            }
+            MenuItem::Slider(name, get, _) => {
+                format!("{}: {:.0}%", locale.get(name), get(world) * 100.0)
+            }
+            MenuItem::Choice(name, get, _) => format!("{}: {}", locale.get(name), get(world)),
+            MenuItem::Back => locale.get("menu.back").to_string(),
+            MenuItem::Text(get, _) => return get(world),
+        };
+        (label, Palette::Item)
+    }
+}
+
+pub struct Menu {
+    pub title: &'static str,
+    pub items: Vec<MenuItem>,
+    // Help lines under the items
+    pub footer: Vec<&'static str>,
+}
+impl Menu {
+    fn first_pick(&self) -> usize {
+        self.items.iter().position(MenuItem::can_pick).unwrap_or(0)
+    }
+
+    // The next item that can be picked, `step` items at a time
+    fn next_pick(&self, from: usize, step: usize) -> usize {
+        let count = self.items.len();
+        let mut index = from;
+        for _ in 0..count {
+            index = (index + step) % count;
+            if self.items[index].can_pick() {
+                return index;
+            }
+        }
+        from
+    }
+}
+
+// Open menus, the last one is shown. A screen inserts this to show a menu and
+// runs `destroy_menu` when it is left.
+pub struct MenuStack {
+    menus: Vec<(Menu, usize)>,
+    // What is on screen, redrawn when it differs
+    drawn: Option<(usize, Vec<(String, Palette)>)>,
+    // Run by Escape or Back on the first menu, a screen of its own leaves with it
+    leave: Option<Action>,
+}
+impl MenuStack {
+    pub fn new(menu: Menu) -> Self {
+        let selected = menu.first_pick();
+        MenuStack {
+            menus: vec![(menu, selected)],
+            drawn: None,
+            leave: None,
+        }
+    }
+
+    pub fn starting_at(mut self, index: usize) -> Self {
+        self.menus[0].1 = index;
+        self
+    }
+
+    pub fn leaving(mut self, run: impl Fn(&mut World) + Send + Sync + 'static) -> Self {
+        self.leave = Some(Box::new(run));
+        self
+    }
+}
+
+#[derive(Component, Debug)]
+struct MenuText;
+
+// Index of the item a text shows
+#[derive(Component, Debug)]
+struct MenuEntry(usize);
+
+// What the mouse or a finger did to the items this frame. A press also says
+// if it was right of the middle of the item, to move sliders up.
+#[derive(Debug, Default)]
+struct MenuPointer {
+    hover: Option<usize>,
+    press: Option<(usize, bool)>,
+}
+
+pub struct MenuPlugin;
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuPointer>()
+            .add_system(point_at_menu)
+            .add_system(update_menu.exclusive_system().at_end());
+    }
+}
+
+pub fn resource<T: Send + Sync + 'static>(world: &World) -> &T {
+    match world.get_resource::<T>() {
+        Some(resource) => resource,
+        None => panic!("HOW DID WE EVEN GET HERE!?!? A menu item wants a missing resource"),
+    }
+}
+
+pub fn resource_mut<T: Send + Sync + 'static>(world: &mut World) -> Mut<T> {
+    match world.get_resource_mut::<T>() {
+        Some(resource) => resource,
+        None => panic!("HOW DID WE EVEN GET HERE!?!? A menu item wants a missing resource"),
+    }
+}
+
+// Exclusive so item callbacks can run right away with the world
+fn update_menu(world: &mut World) {
+    if world.get_resource::<MenuStack>().is_none() {
+        return;
+    }
+    let keys = resource::<Input<KeyCode>>(world);
+    let up = keys.just_pressed(KeyCode::Up);
+    let down = keys.just_pressed(KeyCode::Down);
+    let mut left = keys.just_pressed(KeyCode::Left);
+    let mut right = keys.just_pressed(KeyCode::Right);
+    let mut select = keys.just_pressed(KeyCode::Space);
+    let escape = keys.just_pressed(KeyCode::Escape);
+    let pointer = std::mem::take(&mut *resource_mut::<MenuPointer>(world));
+
+    world.resource_scope(|world, mut stack: Mut<MenuStack>| {
+        let depth = stack.menus.len();
+        let (menu, selected) = match stack.menus.last_mut() {
+            Some(top) => top,
+            None => panic!("HOW DID WE EVEN GET HERE!?!? A menu stack without menus"),
+        };
+        let mut sounds = Vec::new();
+        let mut open = None;
+        let mut close = escape;
+
+        let count = menu.items.len();
+        if up {
+            *selected = menu.next_pick(*selected, count - 1);
+            sounds.push(Sound::MenuMove);
+        } else if down {
+            *selected = menu.next_pick(*selected, 1);
+            sounds.push(Sound::MenuMove);
+        }
+        // Pointing picks the item, pressing also uses it like the keys would
+        let can_pick = |index: usize| index < count && menu.items[index].can_pick();
+        match pointer.hover {
+            Some(index) if can_pick(index) && index != *selected => {
+                *selected = index;
+                sounds.push(Sound::MenuMove);
+            }
+            _ => (),
+        }
+        if let Some((index, right_side)) = pointer.press.filter(|(index, _)| can_pick(*index)) {
+            *selected = index;
+            match menu.items[index] {
+                MenuItem::Slider(..) if right_side => right = true,
+                MenuItem::Slider(..) => left = true,
+                _ => select = true,
+            }
+        }
+        match &menu.items[*selected] {
+            MenuItem::Slider(_, get, set) if left || right => {
+                let step = if right { SLIDER_STEP } else { -SLIDER_STEP };
+                let value = (get(world) + step).clamp(0.0, 1.0);
+                set(world, value);
+                sounds.push(Sound::MenuMove);
+            }
+            _ if !select => (),
+            MenuItem::Action(_, run) => run(world),
+            MenuItem::Toggle(_, get, set) => {
+                let value = get(world);
+                set(world, !value);
+            }
+            MenuItem::Slider(..) => (),
+            MenuItem::Choice(_, _, next) => next(world),
+            MenuItem::Submenu(_, submenu) => open = Some(submenu()),
+            MenuItem::Back => close = true,
+            MenuItem::Text(_, Some(run)) => run(world),
+            MenuItem::Text(_, None) => (),
+        }
+        // The first menu only closes when the screen has somewhere to go
+        close &= depth > 1 || stack.leave.is_some();
+        if select || close {
+            sounds.push(Sound::MenuSelect);
+        }
+
+        if let Some(submenu) = open {
+            let selected = submenu.first_pick();
+            stack.menus.push((submenu, selected));
+        } else if close && depth > 1 {
+            stack.menus.pop();
+        } else if close {
+            if let Some(leave) = &stack.leave {
+                leave(world);
+            }
+        }
+        if let Some(mut events) = world.get_resource_mut::<Events<PlaySound>>() {
+            for sound in sounds {
+                events.send(PlaySound(sound));
+            }
+        }
+
+        // Values can change outside the menu too, a theme finishing to load
+        // renames it, so labels are compared every frame. A new language
+        // changes them all, the title and footer come along with the redraw.
+        let (menu, selected) = &stack.menus[stack.menus.len() - 1];
+        let labels: Vec<(String, Palette)> =
+            menu.items.iter().map(|item| item.label(world)).collect();
+        let shown = Some((*selected, labels));
+        if stack.drawn != shown {
+            draw_menu(world, menu, shown.as_ref().unwrap());
+            stack.drawn = shown;
+        }
+    });
+}
+
+fn draw_menu(world: &mut World, menu: &Menu, (selected, labels): &(usize, Vec<(String, Palette)>)) {
+    let old: Vec<Entity> = world
+        .query_filtered::<Entity, With<MenuText>>()
+        .iter(world)
+        .collect();
+    for ent in old {
+        world.despawn(ent);
+    }
+
+    let font = resource::<GameAssets>(world).font.clone();
+    let theme = resource::<Theme>(world).clone();
+    let text_scale = resource::<Config>(world).text_scale;
+    let title = resource::<Locale>(world).get(menu.title).to_string();
+    let footer: Vec<String> = menu
+        .footer
+        .iter()
+        .map(|line| resource::<Locale>(world).get(line).to_string())
+        .collect();
+    let text_alignment = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+    let mut spawn_text = |text: String, font_size: f32, palette: Palette, y: f32| {
+        world
+            .spawn()
+            .insert_bundle(Text2dBundle {
+                text: Text::with_section(
+                    text,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: font_size * text_scale,
+                        color: theme.color(palette),
+                    },
+                    text_alignment,
+                ),
+                transform: Transform::from_xyz(0.0, y, 10.0),
+                ..Default::default()
+            })
+            .insert(MenuText)
+            .insert(Themed(palette))
+            .id()
+    };
+
+    spawn_text(title, 120.0, Palette::Title, TITLE_Y);
+    let gaps = labels.len().saturating_sub(1) as f32;
+    let spacing = ITEM_SPACING.min(ITEMS_HEIGHT / gaps);
+    let top = ITEMS_Y + spacing * gaps / 2.0;
+    let entries: Vec<Entity> = labels
+        .iter()
+        .enumerate()
+        .map(|(index, (label, palette))| {
+            let y = top - spacing * index as f32;
+            if index == *selected {
+                spawn_text(format!("- {} -", label), 40.0, Palette::Selected, y)
+            } else {
+                spawn_text(label.clone(), 32.0, *palette, y)
+            }
+        })
+        .collect();
+    for (index, line) in footer.into_iter().enumerate() {
+        let y = FOOTER_Y - FOOTER_SPACING * index as f32;
+        spawn_text(line, 30.0, Palette::Controls, y);
+    }
+    for (index, ent) in entries.into_iter().enumerate() {
+        world.entity_mut(ent).insert(MenuEntry(index));
+    }
+}
+
+// A click or a tap was let go this frame. Letting go instead of pressing keeps
+// the same tap from reaching the next screen too.
+pub fn tapped(mouse_input: &Input<MouseButton>, touches: &Touches) -> bool {
+    mouse_input.just_released(MouseButton::Left) || touches.iter_just_released().next().is_some()
+}
+
+// Where a click or a tap was let go this frame, in world units
+fn released_at(
+    win: &Window,
+    game_size: &GameSize,
+    mouse_input: &Input<MouseButton>,
+    touches: &Touches,
+) -> Option<Vec2> {
+    // On the web taps come in as mouse clicks
+    let position = if mouse_input.just_released(MouseButton::Left) {
+        win.cursor_position()
+    } else {
+        touches
+            .iter_just_released()
+            .last()
+            .map(|touch| touch.position())
+    };
+    position.map(|position| game_size.window_to_world(win, position))
+}
+
+// The laid out text under a point and how far from its middle the point is,
+// the closest one where texts overlap
+fn text_at<'a, T>(
+    point: Vec2,
+    texts: impl Iterator<Item = (T, &'a Text2dSize, &'a GlobalTransform)>,
+) -> Option<(T, Vec2)> {
+    texts
+        .filter_map(|(value, size, transform)| {
+            let offset = point - transform.translation.truncate();
+            let inside =
+                offset.x.abs() <= size.size.width / 2.0 && offset.y.abs() <= size.size.height / 2.0;
+            inside.then(|| (value, offset))
+        })
+        .min_by(|(_, a), (_, b)| a.y.abs().total_cmp(&b.y.abs()))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn point_at_menu(
+    stack: Option<Res<MenuStack>>,
+    windows: Res<Windows>,
+    game_size: Res<GameSize>,
+    mouse_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut cursor_reader: EventReader<CursorMoved>,
+    mut pointer: ResMut<MenuPointer>,
+    entry_query: Query<(&MenuEntry, &Text2dSize, &GlobalTransform)>,
+) {
+    let moved_to = cursor_reader.iter().last().map(|moved| moved.position);
+    if stack.is_none() {
+        return;
+    }
+    let win = match windows.get_primary() {
+        Some(win) => win,
+        None => return,
+    };
+    let entries = || {
+        entry_query
+            .iter()
+            .map(|(entry, size, transform)| (entry.0, size, transform))
+    };
+
+    // Hovering only counts when the mouse moves, so it doesn't fight the keys
+    if let Some(position) = moved_to {
+        let point = game_size.window_to_world(win, position);
+        pointer.hover = text_at(point, entries()).map(|(index, _)| index);
+    }
+    if let Some(point) = released_at(win, &game_size, &mouse_input, &touches) {
+        pointer.press = text_at(point, entries()).map(|(index, offset)| (index, offset.x > 0.0));
+    }
+}
+
+pub fn destroy_menu(mut commands: Commands, query: Query<Entity, With<MenuText>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+
+    commands.remove_resource::<MenuStack>();
+}
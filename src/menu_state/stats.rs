@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use super::main_menu::go_to;
+use super::menu::{destroy_menu, Menu, MenuItem, MenuStack};
+use super::AppState;
+use crate::locale::Locale;
+use crate::play_state::{snake::DeathCause, stats::LifetimeStats};
+use crate::theme::Palette;
+
+pub struct StatsScreenPlugin;
+impl Plugin for StatsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::StatsState).with_system(spawn_stats))
+            .add_system_set(SystemSet::on_exit(AppState::StatsState).with_system(destroy_menu));
+    }
+}
+
+// Share of the ticks spent in each quarter of the arena
+fn quadrants(quadrants: &[usize; 4]) -> String {
+    let total: usize = quadrants.iter().sum();
+    if total == 0 {
+        return "-".to_string();
+    }
+    quadrants
+        .iter()
+        .map(|ticks| format!("{}", ticks * 100 / total))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn death_key(cause: DeathCause) -> &'static str {
+    match cause {
+        DeathCause::Boundary => "death.boundary",
+        DeathCause::Body => "death.body",
+        DeathCause::Wall => "death.wall",
+        DeathCause::Hazard => "death.hazard",
+        DeathCause::ClosingWall => "death.closing_wall",
+    }
+}
+
+fn seconds(seconds: f64) -> String {
+    format!("{:.0}s", seconds)
+}
+
+fn spawn_stats(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    mut key_input: ResMut<Input<KeyCode>>,
+    lifetime: Res<LifetimeStats>,
+) {
+    // The key that opened this screen must not also close it
+    key_input.reset(KeyCode::Space);
+
+    let text = |key: &str| locale.get(key).to_string();
+    let last = lifetime.last_run.clone().unwrap_or_default();
+    let last_death = match last.death {
+        Some(cause) => text(death_key(cause)),
+        None => "-".to_string(),
+    };
+    let top_death = match lifetime.deaths.iter().max_by_key(|(_, count)| **count) {
+        Some((cause, _)) => text(death_key(*cause)),
+        None => "-".to_string(),
+    };
+    let rows = [
+        ("stats.runs", "-".to_string(), lifetime.runs.to_string()),
+        (
+            "stats.food",
+            last.food.to_string(),
+            lifetime.food.to_string(),
+        ),
+        (
+            "stats.time",
+            seconds(last.seconds),
+            seconds(lifetime.seconds),
+        ),
+        (
+            "stats.turns",
+            last.turns.to_string(),
+            lifetime.turns.to_string(),
+        ),
+        (
+            "stats.distance",
+            last.distance.to_string(),
+            lifetime.distance.to_string(),
+        ),
+        (
+            "stats.length",
+            last.length.to_string(),
+            lifetime.best_length.to_string(),
+        ),
+        (
+            "stats.quarters",
+            quadrants(&last.quadrants),
+            quadrants(&lifetime.quadrants),
+        ),
+        ("stats.death", last_death, top_death),
+    ];
+
+    // Every row reads the last run and then the lifetime, like the first line says
+    let header = format!("{} / {}", text("stats.last_run"), text("stats.lifetime"));
+    let mut items = vec![MenuItem::line(move |_| (header.clone(), Palette::Header))];
+    for (label, last_value, lifetime_value) in rows {
+        let row = format!("{}: {} / {}", text(label), last_value, lifetime_value);
+        items.push(MenuItem::line(move |_| (row.clone(), Palette::Item)));
+    }
+    items.push(MenuItem::Back);
+    let menu = Menu {
+        title: "stats.title",
+        items,
+        footer: vec!["settings.footer.back"],
+    };
+    commands.insert_resource(
+        MenuStack::new(menu).leaving(|world| go_to(world, AppState::MainMenuState)),
+    );
+}
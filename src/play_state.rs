@@ -1,28 +1,179 @@
-use super::AppState;
+use super::{
+    sound::{PlaySound, Sound},
+    storage, AppState,
+};
 use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
+use achievements::AchievementsPlugin;
 use arena::ArenaPlugin;
+use camera::CameraPlugin;
+use campaign::CampaignPlugin;
 use food::FoodPlugin;
+use hazard::HazardPlugin;
+use level::{CurrentLevel, Level};
+use portal::PortalPlugin;
+use replay::ReplayPlugin;
 use score_board::ScoreBoardPlugin;
+use skin::{SkinChoice, SkinPlugin, SKIN_FILE};
 use snake::SnakePlugin;
+use stats::StatsPlugin;
+use survival::SurvivalPlugin;
+use time_attack::TimeAttackPlugin;
+use touch::TouchPlugin;
+use wall::WallPlugin;
 
-mod arena;
-mod food;
+pub mod achievements;
+pub mod arena;
+pub mod camera;
+pub mod campaign;
+pub mod food;
+mod hazard;
+pub mod level;
+mod portal;
+pub mod replay;
 mod score_board;
-mod snake;
+pub mod skin;
+pub mod snake;
+pub mod stats;
+pub mod survival;
+pub mod time_attack;
+pub mod touch;
+mod wall;
+
+// Which rules the next run is played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    Classic,
+    Campaign(usize),
+    Playtest,
+    TimeAttack,
+    Survival,
+}
+
+pub const RULES_FILE: &str = "rules.ron";
+
+// Optional rules, saved between sessions
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Rules {
+    // Biting the body cuts the tail off instead of ending the game
+    pub tail_cut: bool,
+}
+
+// Every random pick of a run. It starts over when a run ends, so with a seed
+// every run picks the same.
+#[derive(Debug)]
+pub struct GameRng {
+    pub rng: StdRng,
+    // Seed of the run, picked at random unless one was given
+    pub seed: u64,
+    fixed: bool,
+}
+impl GameRng {
+    pub fn new(seed: Option<u64>) -> Self {
+        GameRng {
+            fixed: seed.is_some(),
+            ..GameRng::once(seed.unwrap_or_else(rand::random))
+        }
+    }
+
+    // Only the next run picks from the seed, the ones after are random again
+    pub fn once(seed: u64) -> Self {
+        GameRng {
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            fixed: false,
+        }
+    }
+}
+
+// The fixed timestep systems don't run while paused
+pub struct Paused(pub bool);
 
 pub struct PlayStatePlugin;
 impl Plugin for PlayStatePlugin {
     fn build(&self, app: &mut App) {
+        app.insert_resource(CurrentLevel(Level::default()))
+            .insert_resource(GameMode::Classic)
+            .insert_resource(storage::load::<Rules>(RULES_FILE))
+            .insert_resource(storage::load::<SkinChoice>(SKIN_FILE))
+            .insert_resource(GameRng::new(None))
+            .insert_resource(Paused(false));
+
         app.add_plugin(SnakePlugin)
             .add_plugin(ArenaPlugin)
             .add_plugin(FoodPlugin)
-            .add_plugin(ScoreBoardPlugin);
+            .add_plugin(PortalPlugin)
+            .add_plugin(HazardPlugin)
+            .add_plugin(WallPlugin)
+            .add_plugin(CampaignPlugin)
+            .add_plugin(TimeAttackPlugin)
+            .add_plugin(SurvivalPlugin)
+            .add_plugin(AchievementsPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(ScoreBoardPlugin)
+            .add_plugin(SkinPlugin)
+            .add_plugin(CameraPlugin)
+            .add_plugin(TouchPlugin)
+            .add_plugin(ReplayPlugin);
+
+        app.add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(unpause))
+            .add_system_set(
+                SystemSet::on_update(AppState::PlayState)
+                    .with_system(leave_play)
+                    .with_system(toggle_pause),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::PlayState).with_system(restart_rng));
     }
 }
 
-fn is_in_play_state_chain(In(input): In<ShouldRun>, state: Res<State<AppState>>) -> ShouldRun {
-    if state.current() == &AppState::PlayState {
+// Go back to wherever the run was started from
+fn leave_play(
+    key_input: Res<Input<KeyCode>>,
+    game_mode: Res<GameMode>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    if key_input.just_pressed(KeyCode::Escape) {
+        let next_state = match *game_mode {
+            GameMode::Classic | GameMode::TimeAttack | GameMode::Survival => {
+                AppState::MainMenuState
+            }
+            GameMode::Campaign(_) => AppState::LevelSelectState,
+            GameMode::Playtest => AppState::EditorState,
+        };
+        // A tick on this frame may have queued the end of the run already
+        app_state.overwrite_set(next_state).unwrap();
+    }
+}
+
+fn unpause(mut paused: ResMut<Paused>, mut key_input: ResMut<Input<KeyCode>>) {
+    // The key that started the run must not also pause it
+    key_input.reset(KeyCode::Space);
+    paused.0 = false;
+}
+
+fn toggle_pause(
+    key_input: Res<Input<KeyCode>>,
+    mut paused: ResMut<Paused>,
+    mut sound_writer: EventWriter<PlaySound>,
+) {
+    if key_input.just_pressed(KeyCode::Space) {
+        paused.0 = !paused.0;
+        sound_writer.send(PlaySound(Sound::Pause));
+    }
+}
+
+fn restart_rng(mut rng: ResMut<GameRng>) {
+    *rng = GameRng::new(rng.fixed.then(|| rng.seed));
+}
+
+fn is_in_play_state_chain(
+    In(input): In<ShouldRun>,
+    state: Res<State<AppState>>,
+    paused: Res<Paused>,
+) -> ShouldRun {
+    if state.current() == &AppState::PlayState && !paused.0 {
         input
     } else {
         ShouldRun::No
@@ -36,4 +187,3 @@ fn is_in_play_state(state: Res<State<AppState>>) -> ShouldRun {
         ShouldRun::No
     }
 }
-
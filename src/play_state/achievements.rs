@@ -0,0 +1,222 @@
+use super::{
+    arena::{ArenaSize, Position},
+    campaign::Key,
+    hazard::Hazard,
+    portal::Portal,
+    snake::{AmountBodyParts, EatEvent, GameOver, TurnEvent},
+    wall::Wall,
+    AppState, GameMode,
+};
+use crate::config::Config;
+use crate::loading_state::GameAssets;
+use crate::locale::Locale;
+use crate::storage;
+use crate::theme::{Palette, Theme, Themed};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+const ACHIEVEMENTS: &str = include_str!("../../assets/achievements.ron");
+const UNLOCKED_FILE: &str = "achievements.ron";
+const TOAST_TIME: f32 = 3.0;
+
+// What has to happen in a single run to unlock an achievement
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Condition {
+    EatFood(usize),
+    ReachLength(usize),
+    EatWithoutTurning(usize),
+    SurviveSeconds(f32),
+    FullBoard,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: Condition,
+}
+impl Achievement {
+    // The file has the English text, string tables can translate it by id
+    pub fn name<'a>(&'a self, locale: &'a Locale) -> &'a str {
+        locale.get_or(&format!("achievement.{}.name", self.id), &self.name)
+    }
+
+    pub fn description<'a>(&'a self, locale: &'a Locale) -> &'a str {
+        locale.get_or(
+            &format!("achievement.{}.description", self.id),
+            &self.description,
+        )
+    }
+}
+
+pub struct Achievements(pub Vec<Achievement>);
+
+// Ids of the unlocked achievements, saved on disk
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UnlockedAchievements {
+    pub ids: Vec<String>,
+}
+
+// Everything the conditions look at, reset on every death
+#[derive(Debug, Default)]
+pub struct RunTracker {
+    ticks: usize,
+    eaten: usize,
+    eaten_since_turn: usize,
+}
+
+// Carries the id of the achievement
+pub struct AchievementUnlocked(pub String);
+
+#[derive(Component, Debug)]
+struct Toast(Timer);
+
+// Achievements are checked by the snake fixed timestep set, see `SnakePlugin`
+pub struct AchievementsPlugin;
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        let achievements: Vec<Achievement> =
+            ron::from_str(ACHIEVEMENTS).expect("Bundled achievements are malformed");
+        app.add_event::<AchievementUnlocked>()
+            .insert_resource(Achievements(achievements))
+            .insert_resource(storage::load::<UnlockedAchievements>(UNLOCKED_FILE))
+            .init_resource::<RunTracker>()
+            .add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(reset_tracker))
+            // Toasts are shown over whatever state the game is in
+            .add_system(spawn_toasts)
+            .add_system(fade_toasts);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn track_achievements(
+    mut eat_reader: EventReader<EatEvent>,
+    mut turn_reader: EventReader<TurnEvent>,
+    mut game_over_reader: EventReader<GameOver>,
+    mut event_writer: EventWriter<AchievementUnlocked>,
+    mut tracker: ResMut<RunTracker>,
+    mut unlocked: ResMut<UnlockedAchievements>,
+    achievements: Res<Achievements>,
+    game_mode: Res<GameMode>,
+    amount_body_parts: Res<AmountBodyParts>,
+    arena_size: Res<ArenaSize>,
+    config: Res<Config>,
+    blocked_query: Query<&Position, Or<(With<Wall>, With<Portal>, With<Key>)>>,
+    hazard_query: Query<&Hazard>,
+) {
+    tracker.ticks += 1;
+    if turn_reader.iter().next().is_some() {
+        tracker.eaten_since_turn = 0;
+    }
+    let eaten = eat_reader.iter().count();
+    tracker.eaten += eaten;
+    tracker.eaten_since_turn += eaten;
+    let died = game_over_reader.iter().next().is_some();
+
+    // Editor levels would make these too easy
+    if *game_mode != GameMode::Playtest && !died {
+        let length = amount_body_parts.0 + 1;
+        // The snake can never lie on a hazard's path
+        let blocked: HashSet<Position> = blocked_query
+            .iter()
+            .copied()
+            .chain(
+                hazard_query
+                    .iter()
+                    .flat_map(|hazard| hazard.path().iter().copied()),
+            )
+            .collect();
+        let free_cells =
+            ((arena_size.width * arena_size.height) as usize).saturating_sub(blocked.len());
+        let mut changed = false;
+        for achievement in achievements.0.iter() {
+            if unlocked.ids.contains(&achievement.id) {
+                continue;
+            }
+            let reached = match achievement.condition {
+                Condition::EatFood(target) => tracker.eaten >= target,
+                Condition::ReachLength(target) => length >= target,
+                Condition::EatWithoutTurning(target) => tracker.eaten_since_turn >= target,
+                Condition::SurviveSeconds(seconds) => {
+                    tracker.ticks as f64 * config.tick_step >= seconds as f64
+                }
+                Condition::FullBoard => length >= free_cells,
+            };
+            if reached {
+                unlocked.ids.push(achievement.id.clone());
+                event_writer.send(AchievementUnlocked(achievement.id.clone()));
+                changed = true;
+            }
+        }
+        if changed {
+            storage::save(UNLOCKED_FILE, &*unlocked);
+        }
+    }
+
+    if died {
+        *tracker = RunTracker::default();
+    }
+}
+
+fn reset_tracker(mut tracker: ResMut<RunTracker>) {
+    *tracker = RunTracker::default();
+}
+
+fn spawn_toasts(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    config: Res<Config>,
+    theme: Res<Theme>,
+    locale: Res<Locale>,
+    achievements: Res<Achievements>,
+    mut event_reader: EventReader<AchievementUnlocked>,
+    toast_query: Query<&Toast>,
+) {
+    // New toasts stack above the ones still showing
+    let mut stacked = toast_query.iter().count();
+    for unlocked in event_reader.iter() {
+        let achievement = match achievements.0.iter().find(|a| a.id == unlocked.0) {
+            Some(achievement) => achievement,
+            None => panic!("HOW DID WE EVEN GET HERE!?!? Unlocked an unknown achievement"),
+        };
+        let font = game_assets.font.clone();
+        let text_style = TextStyle {
+            font,
+            font_size: 25.0 * config.text_scale,
+            color: theme.highlight,
+        };
+        let text_alignment = TextAlignment {
+            vertical: VerticalAlign::Center,
+            horizontal: HorizontalAlign::Center,
+        };
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    locale.fill("awards.toast", achievement.name(&locale)),
+                    text_style,
+                    text_alignment,
+                ),
+                transform: Transform::from_xyz(0.0, -260.0 + 30.0 * stacked as f32, 50.0),
+                ..Default::default()
+            })
+            .insert(Toast(Timer::from_seconds(TOAST_TIME, false)))
+            .insert(Themed(Palette::Highlight));
+        stacked += 1;
+    }
+}
+
+fn fade_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Toast, &mut Text)>,
+) {
+    for (ent, mut toast, mut text) in query.iter_mut() {
+        toast.0.tick(time.delta());
+        text.sections[0].style.color.set_a(1.0 - toast.0.percent());
+        if toast.0.finished() {
+            commands.entity(ent).despawn();
+        }
+    }
+}
@@ -1,18 +1,28 @@
-use super::{is_in_play_state, AppState};
+use super::{is_in_play_state, level::CurrentLevel, snake::SnakeTick, AppState, Paused};
+use crate::config::Config;
+use crate::theme::{Palette, Theme, Themed};
 use crate::GameSize;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::ops::Add;
 
-pub const ARENA_WIDTH: i32 = 40;
-pub const ARENA_HEIGHT: i32 = 30;
-pub const CEL_SIZE: f32 = 20.0;
-
+// Taken from the level when play starts, nothing else knows how big the arena is
 pub struct ArenaSize {
     pub width: i32,
     pub height: i32,
 }
+impl ArenaSize {
+    pub fn contains(&self, pos: &Position) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height
+    }
+
+    // Cells are as big as fits in the game size, with the arena centered
+    pub fn cell_size(&self, game_size: &GameSize) -> f32 {
+        (game_size.width / self.width as f32).min(game_size.height / self.height as f32)
+    }
+}
 
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -27,10 +37,20 @@ impl Add for Position {
     }
 }
 
+// Where a moving entity was before the last tick, it is drawn sliding from
+// there to its current position
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PreviousPosition(pub Position);
+
+// Covers the game area around an arena that is not as wide or as tall as it
+#[derive(Component, Debug)]
+struct ArenaEdge;
+
 pub struct ArenaPlugin;
 impl Plugin for ArenaPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(setup_arena))
+            .add_system_set(SystemSet::on_exit(AppState::PlayState).with_system(destroy_arena))
             .add_system_set_to_stage(
                 CoreStage::PostUpdate,
                 SystemSet::new()
@@ -40,26 +60,144 @@ impl Plugin for ArenaPlugin {
     }
 }
 
-fn setup_arena(mut commands: Commands) {
-    commands.insert_resource(ArenaSize {
-        width: ARENA_WIDTH,
-        height: ARENA_HEIGHT,
-    })
+fn setup_arena(
+    mut commands: Commands,
+    level: Res<CurrentLevel>,
+    game_size: Res<GameSize>,
+    theme: Res<Theme>,
+) {
+    let arena_size = ArenaSize {
+        width: level.0.width,
+        height: level.0.height,
+    };
+    let cell = arena_size.cell_size(&game_size);
+    let arena = Vec2::new(arena_size.width as f32, arena_size.height as f32) * cell;
+    let edge_size = game_size.width.max(game_size.height);
+    for side in [Vec2::X, -Vec2::X, Vec2::Y, -Vec2::Y] {
+        let position = side * (arena + Vec2::splat(edge_size)) / 2.0;
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: theme.letterbox,
+                    custom_size: Some(Vec2::splat(edge_size)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(0.5)),
+                ..Default::default()
+            })
+            .insert(ArenaEdge)
+            .insert(Themed(Palette::Letterbox));
+    }
+    commands.insert_resource(arena_size);
+}
+
+// Everything placed on the arena has a position
+fn destroy_arena(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<Position>, With<ArenaEdge>)>>,
+) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+}
+
+// Runs first on every tick, see `SnakePlugin`
+pub fn store_previous_positions(mut query: Query<(&Position, &mut PreviousPosition)>) {
+    for (pos, mut previous) in query.iter_mut() {
+        previous.0 = *pos;
+    }
 }
 
 fn update_position(
     arena_size: Res<ArenaSize>,
     game_size: Res<GameSize>,
-    mut query: Query<(&Position, &mut Transform)>,
+    config: Res<Config>,
+    snake_tick: Res<SnakeTick>,
+    paused: Res<Paused>,
+    mut query: Query<(&Position, Option<&PreviousPosition>, &mut Transform)>,
 ) {
-    fn convert(pos: f32, g_size: f32, a_size: f32) -> f32 {
-        pos / a_size * g_size - (g_size / 2.0) + (CEL_SIZE * 0.5)
+    fn convert(pos: f32, a_size: i32, cell: f32) -> f32 {
+        (pos - (a_size - 1) as f32 / 2.0) * cell
+    }
+    // Sprites are sized in cells, they are scaled up to the cell size here
+    let cell = arena_size.cell_size(&game_size);
+    // How far we are into the next tick, the time still adds up while paused
+    let progress = if paused.0 {
+        1.0
+    } else {
+        (snake_tick.overstep_percentage(config.tick_step) as f32).min(1.0)
+    };
+    for (pos, previous, mut transform) in query.iter_mut() {
+        // Every part only ever steps to a neighbouring cell, so sliding
+        // each one on its own follows corners. A jump through a portal
+        // is not a step and is drawn at once, on both sides of the portal.
+        let from = match previous {
+            Some(previous) if (previous.0.x - pos.x).abs() + (previous.0.y - pos.y).abs() == 1 => {
+                previous.0
+            }
+            _ => *pos,
+        };
+        let x = from.x as f32 + (pos.x - from.x) as f32 * progress;
+        let y = from.y as f32 + (pos.y - from.y) as f32 * progress;
+        transform.translation.x = convert(x, arena_size.width, cell);
+        // Row 0 is at the top, world y goes up
+        transform.translation.y = -convert(y, arena_size.height, cell);
+        transform.scale = Vec3::new(cell, cell, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTHS: [i32; 8] = [5, 6, 7, 10, 13, 40, 99, 200];
+    const HEIGHTS: [i32; 8] = [5, 6, 7, 10, 13, 30, 77, 150];
+
+    fn sizes() -> impl Iterator<Item = ArenaSize> {
+        WIDTHS.into_iter().flat_map(|width| {
+            HEIGHTS
+                .into_iter()
+                .map(move |height| ArenaSize { width, height })
+        })
     }
-    for (pos, mut transform) in query.iter_mut() {
-        transform.translation = Vec3::new(
-            convert(pos.x as f32, game_size.width, arena_size.width as f32),
-            -convert(pos.y as f32, game_size.height, arena_size.height as f32),
-            10.0,
-        );
+
+    #[test]
+    fn cells_fit_the_game_size() {
+        let game_size = GameSize {
+            width: 800.0,
+            height: 600.0,
+        };
+        for arena_size in sizes() {
+            let cell = arena_size.cell_size(&game_size);
+            let width = arena_size.width as f32 * cell;
+            let height = arena_size.height as f32 * cell;
+            assert!(width <= game_size.width + 0.01);
+            assert!(height <= game_size.height + 0.01);
+            // One side fills the game size, else the cells could be bigger
+            assert!(
+                (width - game_size.width).abs() < 0.01 || (height - game_size.height).abs() < 0.01
+            );
+        }
+    }
+
+    #[test]
+    fn contains_every_cell_and_nothing_else() {
+        for arena_size in sizes() {
+            for x in -1..=arena_size.width {
+                for y in -1..=arena_size.height {
+                    let inside =
+                        (0..arena_size.width).contains(&x) && (0..arena_size.height).contains(&y);
+                    assert_eq!(
+                        arena_size.contains(&Position { x, y }),
+                        inside,
+                        "({}, {}) on {}x{}",
+                        x,
+                        y,
+                        arena_size.width,
+                        arena_size.height
+                    );
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,252 @@
+use super::{
+    arena::{ArenaSize, Position},
+    snake::Head,
+    AppState,
+};
+use crate::theme::{Theme, Themed};
+use crate::{storage, GameSize};
+use bevy::{
+    prelude::*,
+    render::{
+        camera::Camera,
+        render_resource::{Extent3d, FilterMode, TextureDimension, TextureFormat},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+pub const CAMERA_FILE: &str = "camera.ron";
+// How much of the game area is in view, smaller is closer
+const ZOOM_LEVELS: [f32; 3] = [1.0, 0.5, 0.25];
+// How fast the camera catches up with the head, higher is snappier
+const FOLLOW_SPEED: f32 = 6.0;
+// The mini-map fits in this box at the top right of the game area
+const MINIMAP_WIDTH: f32 = 160.0;
+const MINIMAP_HEIGHT: f32 = 120.0;
+const MINIMAP_MARGIN: f32 = 10.0;
+// World depth of the mini-map, above toasts and below the letterbox
+const MINIMAP_Z: f32 = 60.0;
+
+// How the arena is viewed, saved between sessions. Zooming only happens while
+// following, a fixed camera always shows the whole arena.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CameraSettings {
+    pub follow: bool,
+    pub zoom: usize,
+    pub minimap: bool,
+}
+impl CameraSettings {
+    pub fn next_zoom(&mut self) {
+        self.zoom = (self.zoom + 1) % ZOOM_LEVELS.len();
+    }
+
+    // How many times closer than the whole arena
+    pub fn magnification(&self) -> f32 {
+        1.0 / ZOOM_LEVELS[self.zoom % ZOOM_LEVELS.len()]
+    }
+
+    fn scale(&self) -> f32 {
+        if self.follow {
+            ZOOM_LEVELS[self.zoom % ZOOM_LEVELS.len()]
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct Minimap(Handle<Image>);
+
+pub struct CameraPlugin;
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<CameraSettings>(CAMERA_FILE))
+            .add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(spawn_minimap))
+            .add_system_set(
+                SystemSet::on_update(AppState::PlayState)
+                    .with_system(camera_keys)
+                    .with_system(follow_head)
+                    .with_system(draw_minimap),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::PlayState)
+                    .with_system(reset_camera)
+                    .with_system(destroy_minimap),
+            );
+    }
+}
+
+// C follows the head, Z cycles the zoom and M shows the mini-map
+fn camera_keys(key_input: Res<Input<KeyCode>>, mut settings: ResMut<CameraSettings>) {
+    let mut changed = true;
+    if key_input.just_pressed(KeyCode::C) {
+        settings.follow = !settings.follow;
+    } else if key_input.just_pressed(KeyCode::Z) {
+        settings.next_zoom();
+    } else if key_input.just_pressed(KeyCode::M) {
+        settings.minimap = !settings.minimap;
+    } else {
+        changed = false;
+    }
+    if changed {
+        storage::save(CAMERA_FILE, &*settings);
+    }
+}
+
+// Zooming scales the camera transform, the letterbox bars are children of the
+// camera so they keep framing the game area
+fn follow_head(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    arena_size: Res<ArenaSize>,
+    game_size: Res<GameSize>,
+    head_query: Query<&Transform, (With<Head>, Without<Camera>)>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+) {
+    let scale = settings.scale();
+    let mut target = match head_query.get_single() {
+        Ok(head) if settings.follow => head.translation.truncate(),
+        _ => Vec2::ZERO,
+    };
+    // Nothing outside the arena comes into view, an arena smaller than the
+    // view stays centered
+    let cell = arena_size.cell_size(&game_size);
+    let arena = Vec2::new(arena_size.width as f32, arena_size.height as f32) * cell;
+    let view = Vec2::new(game_size.width, game_size.height) * scale;
+    let room = ((arena - view) / 2.0).max(Vec2::ZERO);
+    target = target.clamp(-room, room);
+
+    let blend = 1.0 - (-FOLLOW_SPEED * time.delta_seconds()).exp();
+    for mut transform in camera_query.iter_mut() {
+        let current = transform.translation.truncate();
+        let next = current + (target - current) * blend;
+        transform.translation.x = next.x;
+        transform.translation.y = next.y;
+        let zoom = transform.scale.x + (scale - transform.scale.x) * blend;
+        transform.scale = Vec3::new(zoom, zoom, 1.0);
+    }
+}
+
+// Menus are laid out for an unmoved camera
+fn reset_camera(mut camera_query: Query<&mut Transform, With<Camera>>) {
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+        transform.scale = Vec3::ONE;
+    }
+}
+
+fn spawn_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    camera_query: Query<(Entity, &Transform), With<Camera>>,
+) {
+    let (camera, camera_transform) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No camera to put the mini-map on"),
+    };
+    // One pixel per cell, sized to the arena once it is known
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor.mag_filter = FilterMode::Nearest;
+    let handle = images.add(image);
+    let minimap = commands
+        .spawn_bundle(SpriteBundle {
+            texture: handle.clone(),
+            transform: Transform::from_xyz(0.0, 0.0, MINIMAP_Z - camera_transform.translation.z),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(Minimap(handle))
+        .id();
+    commands.entity(camera).add_child(minimap);
+}
+
+fn color_bytes(color: Color) -> [u8; 4] {
+    [
+        (color.r() * 255.0) as u8,
+        (color.g() * 255.0) as u8,
+        (color.b() * 255.0) as u8,
+        (color.a() * 255.0) as u8,
+    ]
+}
+
+// Redrawn every frame from everything themed on the arena, the head on top
+fn draw_minimap(
+    settings: Res<CameraSettings>,
+    arena_size: Res<ArenaSize>,
+    game_size: Res<GameSize>,
+    theme: Res<Theme>,
+    mut images: ResMut<Assets<Image>>,
+    mut minimap_query: Query<(&Minimap, &mut Sprite, &mut Transform, &mut Visibility)>,
+    arena_query: Query<(&Position, &Themed, Option<&Head>)>,
+) {
+    let (minimap, mut sprite, mut transform, mut visibility) = match minimap_query.get_single_mut()
+    {
+        Ok(minimap) => minimap,
+        Err(_) => return,
+    };
+    visibility.is_visible = settings.minimap;
+    if !settings.minimap {
+        return;
+    }
+    let image = match images.get_mut(&minimap.0) {
+        Some(image) => image,
+        None => return,
+    };
+    let (width, height) = (arena_size.width as u32, arena_size.height as u32);
+    if image.texture_descriptor.size.width != width
+        || image.texture_descriptor.size.height != height
+    {
+        image.resize(Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        });
+        let size = Vec2::new(width as f32, height as f32);
+        let fitted = size * (Vec2::new(MINIMAP_WIDTH, MINIMAP_HEIGHT) / size).min_element();
+        let corner = Vec2::new(game_size.width, game_size.height) / 2.0;
+        let center = corner - fitted / 2.0 - Vec2::splat(MINIMAP_MARGIN);
+        sprite.custom_size = Some(fitted);
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+
+    let mut background = theme.background;
+    background.set_a(0.8);
+    let background = color_bytes(background);
+    for pixel in image.data.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&background);
+    }
+    // Row 0 is the top of the arena, `update_position` flips y, and the top
+    // row of the image as well, so rows map straight across
+    let mut paint = |pos: &Position, themed: &Themed| {
+        if arena_size.contains(pos) {
+            let start = (pos.y as usize * width as usize + pos.x as usize) * 4;
+            image.data[start..start + 4].copy_from_slice(&color_bytes(theme.color(themed.0)));
+        }
+    };
+    for (pos, themed, head) in arena_query.iter() {
+        if head.is_none() {
+            paint(pos, themed);
+        }
+    }
+    for (pos, themed, head) in arena_query.iter() {
+        if head.is_some() {
+            paint(pos, themed);
+        }
+    }
+}
+
+fn destroy_minimap(mut commands: Commands, query: Query<Entity, With<Minimap>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
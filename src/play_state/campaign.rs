@@ -0,0 +1,164 @@
+use super::{
+    arena::Position,
+    level::{CurrentLevel, Goal, Level},
+    snake::{AmountBodyParts, EatEvent, GameOver, Head},
+    AppState, GameMode,
+};
+use crate::config::Config;
+use crate::storage;
+use crate::theme::{Palette, Theme, Themed};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const PROGRESS_FILE: &str = "campaign.ron";
+
+const CAMPAIGN_LEVELS: [&str; 5] = [
+    include_str!("../../assets/levels/campaign/01.ron"),
+    include_str!("../../assets/levels/campaign/02.ron"),
+    include_str!("../../assets/levels/campaign/03.ron"),
+    include_str!("../../assets/levels/campaign/04.ron"),
+    include_str!("../../assets/levels/campaign/05.ron"),
+];
+pub const CAMPAIGN_LENGTH: usize = CAMPAIGN_LEVELS.len();
+
+pub fn campaign_level(index: usize) -> Level {
+    Level::from_ron(CAMPAIGN_LEVELS[index]).expect("Bundled campaign level is malformed")
+}
+
+// Saved on disk, every level up to the first not cleared one is unlocked
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    pub cleared: usize,
+}
+impl CampaignProgress {
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index <= self.cleared
+    }
+}
+
+// Progress towards the goal of the level being played
+#[derive(Debug, Default)]
+pub struct GoalProgress {
+    eaten: usize,
+    keys: usize,
+    ticks: usize,
+    reached: bool,
+}
+
+#[derive(Component, Debug)]
+pub struct Key;
+
+#[derive(Bundle)]
+struct KeyBundle {
+    key: Key,
+    position: Position,
+    themed: Themed,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+// Keys and goals are updated by the snake fixed timestep set, see `SnakePlugin`
+pub struct CampaignPlugin;
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<CampaignProgress>(PROGRESS_FILE))
+            .init_resource::<GoalProgress>()
+            .add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(reset_goal));
+    }
+}
+
+pub fn reset_goal(
+    mut commands: Commands,
+    level: Res<CurrentLevel>,
+    theme: Res<Theme>,
+    key_query: Query<Entity, With<Key>>,
+) {
+    for ent in key_query.iter() {
+        commands.entity(ent).despawn();
+    }
+    for position in level.0.keys.iter() {
+        commands.spawn_bundle(KeyBundle {
+            key: Key,
+            position: *position,
+            themed: Themed(Palette::Key),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: theme.key,
+                    custom_size: Some(Vec2::new(0.5, 0.5)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 5.0),
+                ..Default::default()
+            },
+        });
+    }
+    commands.insert_resource(GoalProgress::default());
+}
+
+pub fn collect_keys(
+    mut commands: Commands,
+    mut goal_progress: ResMut<GoalProgress>,
+    head_query: Query<&Position, With<Head>>,
+    key_query: Query<(Entity, &Position), With<Key>>,
+) {
+    let head_pos = match head_query.get_single() {
+        Ok(pos) => pos,
+        Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No head for keys"),
+    };
+    for (ent, pos) in key_query.iter() {
+        if pos == head_pos {
+            commands.entity(ent).despawn();
+            goal_progress.keys += 1;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn check_goal(
+    mut eat_reader: EventReader<EatEvent>,
+    mut game_over_reader: EventReader<GameOver>,
+    mut goal_progress: ResMut<GoalProgress>,
+    mut campaign_progress: ResMut<CampaignProgress>,
+    mut game_mode: ResMut<GameMode>,
+    mut level: ResMut<CurrentLevel>,
+    mut app_state: ResMut<State<AppState>>,
+    amount_body_parts: Res<AmountBodyParts>,
+    config: Res<Config>,
+) {
+    goal_progress.ticks += 1;
+    goal_progress.eaten += eat_reader.iter().count();
+    // Dying on the same tick the goal is reached doesn't count
+    if game_over_reader.iter().next().is_some() || goal_progress.reached {
+        return;
+    }
+    let index = match *game_mode {
+        GameMode::Campaign(index) => index,
+        _ => return,
+    };
+    goal_progress.reached = match level.0.goal {
+        Some(Goal::EatFood(amount)) => goal_progress.eaten >= amount,
+        Some(Goal::ReachLength(length)) => amount_body_parts.0 + 1 >= length,
+        Some(Goal::SurviveSeconds(seconds)) => {
+            goal_progress.ticks as f64 * config.tick_step >= seconds as f64
+        }
+        Some(Goal::CollectKeys) => goal_progress.keys >= level.0.keys.len(),
+        None => false,
+    };
+    if !goal_progress.reached {
+        return;
+    }
+    if campaign_progress.cleared <= index {
+        campaign_progress.cleared = index + 1;
+        storage::save(PROGRESS_FILE, &*campaign_progress);
+    }
+    // Play on to the next level, after the last one the level select shows
+    // the whole campaign cleared. The progress is saved whatever else was
+    // queued on this frame.
+    if index + 1 < CAMPAIGN_LENGTH {
+        *game_mode = GameMode::Campaign(index + 1);
+        level.0 = campaign_level(index + 1);
+        app_state.overwrite_restart();
+    } else {
+        app_state.overwrite_set(AppState::LevelSelectState).unwrap();
+    }
+}
@@ -1,14 +1,15 @@
 use super::{
-    arena::{ArenaSize, Position, CEL_SIZE},
-    AppState,
+    arena::{ArenaSize, Position},
+    level::CurrentLevel,
+    snake::GameStages,
+    AppState, GameRng,
 };
-use bevy::{
-    ecs::{schedule::ShouldRun, system::QuerySingleError},
-    prelude::*,
-};
-use rand::prelude::random;
+use crate::theme::{Palette, Theme, Themed};
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use rand::Rng;
+use std::collections::HashSet;
 
-const FOOD_COLOR: Color = Color::rgb(0.7, 0.0, 0.0);
+const RANDOM_TRIES: usize = 100;
 
 #[derive(Component, Debug)]
 pub struct Food;
@@ -17,6 +18,7 @@ pub struct Food;
 struct FoodBundle {
     food: Food,
     position: Position,
+    themed: Themed,
     #[bundle]
     sprite: SpriteBundle,
 }
@@ -24,51 +26,100 @@ struct FoodBundle {
 pub struct FoodPlugin;
 impl Plugin for FoodPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_update(AppState::PlayState).with_system(food_spawner));
+        // Before the tick, so food eaten on one tick is back before the next
+        // and a replay finds the same free cells
+        app.add_system_set(
+            SystemSet::on_update(AppState::PlayState)
+                .with_system(food_spawner.before(GameStages::Update)),
+        );
     }
 }
 
 fn food_spawner(
-    mut commands: Commands,
+    commands: Commands,
     arena_size: Res<ArenaSize>,
+    level: Res<CurrentLevel>,
+    theme: Res<Theme>,
+    rng: ResMut<GameRng>,
     pos_query: Query<&Position>,
     food_query: Query<&Food>,
 ) {
-    match food_query.get_single() {
-        // If there is food
-        Ok(_) => (),
-        // No food
-        Err(QuerySingleError::NoEntities(_)) => spawn_food(commands, arena_size, pos_query),
-        // Multiple foods
-        Err(QuerySingleError::MultipleEntities(_)) => {
-            panic!("HOW DID WE EVEN GET HERE!?!? Multiple food error")
-        }
+    // Only spawn once every food is eaten
+    if food_query.iter().next().is_none() {
+        spawn_food(commands, arena_size, level, theme, rng, pos_query);
     }
 }
 
-pub fn spawn_food(mut commands: Commands, arena_size: Res<ArenaSize>, pos_query: Query<&Position>) {
-    let new_position = || Position {
-        x: (random::<f32>() * arena_size.width as f32) as i32,
-        y: (random::<f32>() * arena_size.height as f32) as i32,
-    };
-    let mut position = new_position();
-    let mut ocupided_position: Vec<Position> = Vec::new();
+// Levels with fixed food spawn all of it again, the rest spawn one at random
+pub fn spawn_food(
+    mut commands: Commands,
+    arena_size: Res<ArenaSize>,
+    level: Res<CurrentLevel>,
+    theme: Res<Theme>,
+    mut rng: ResMut<GameRng>,
+    pos_query: Query<&Position>,
+) {
+    // Every positioned entity (snake, portals, ...) blocks the cell
+    let mut ocupided_position: HashSet<Position> = HashSet::new();
     for pos in pos_query.iter() {
-        ocupided_position.push(*pos);
-    }
-    while ocupided_position.contains(&position) {
-        position = new_position();
+        ocupided_position.insert(*pos);
     }
-    commands.spawn_bundle(FoodBundle {
-        food: Food,
-        position,
-        sprite: SpriteBundle {
-            sprite: Sprite {
-                color: FOOD_COLOR,
-                custom_size: Some(Vec2::new(CEL_SIZE * 0.75, CEL_SIZE * 0.75)),
+    let mut spawn = |position: Position| {
+        commands.spawn_bundle(FoodBundle {
+            food: Food,
+            position,
+            themed: Themed(Palette::Food),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: theme.food,
+                    custom_size: Some(Vec2::new(0.75, 0.75)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
                 ..Default::default()
             },
-            ..Default::default()
-        },
-    });
+        });
+    };
+    if !level.0.food.is_empty() {
+        for position in level.0.food.iter() {
+            if !ocupided_position.contains(position) {
+                spawn(*position);
+            }
+        }
+        return;
+    }
+    if let Some(position) = free_cell(&mut rng, arena_size.width, arena_size.height, |pos| {
+        !ocupided_position.contains(pos)
+    }) {
+        spawn(position);
+    }
+}
+
+// Where random food goes, also used by the headless tournament games so a
+// seed places food the same way in both. A crowded arena picks among the free
+// cells, a full one has no food left to eat.
+pub fn free_cell(
+    rng: &mut GameRng,
+    width: i32,
+    height: i32,
+    is_free: impl Fn(&Position) -> bool,
+) -> Option<Position> {
+    for _ in 0..RANDOM_TRIES {
+        let position = Position {
+            x: rng.rng.gen_range(0..width),
+            y: rng.rng.gen_range(0..height),
+        };
+        if is_free(&position) {
+            return Some(position);
+        }
+    }
+    let free_position: Vec<Position> = (0..width)
+        .flat_map(|x| (0..height).map(move |y| Position { x, y }))
+        .filter(|pos| is_free(pos))
+        .collect();
+    if free_position.is_empty() {
+        None
+    } else {
+        Some(free_position[rng.rng.gen_range(0..free_position.len())])
+    }
 }
@@ -0,0 +1,115 @@
+use super::{
+    arena::{Position, PreviousPosition},
+    level::{CurrentLevel, PathMode},
+    AppState,
+};
+use crate::theme::{Palette, Theme, Themed};
+use bevy::prelude::*;
+
+#[derive(Component, Debug)]
+pub struct Hazard {
+    cells: Vec<Position>,
+    mode: PathMode,
+    index: usize,
+    forward: bool,
+    pub previous: Position,
+}
+impl Hazard {
+    fn new(cells: Vec<Position>, mode: PathMode) -> Hazard {
+        Hazard {
+            previous: cells[0],
+            cells,
+            mode,
+            index: 0,
+            forward: true,
+        }
+    }
+
+    // Every cell it walks
+    pub fn path(&self) -> &[Position] {
+        &self.cells
+    }
+
+    fn step(&mut self) -> Position {
+        self.previous = self.cells[self.index];
+        let last = self.cells.len() - 1;
+        match self.mode {
+            PathMode::Loop => self.index = (self.index + 1) % self.cells.len(),
+            PathMode::BackAndForth if last > 0 => {
+                if self.index == last {
+                    self.forward = false;
+                } else if self.index == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.index += 1;
+                } else {
+                    self.index -= 1;
+                }
+            }
+            PathMode::BackAndForth => (),
+        }
+        self.cells[self.index]
+    }
+
+    fn reset(&mut self) -> Position {
+        self.index = 0;
+        self.forward = true;
+        self.previous = self.cells[0];
+        self.cells[0]
+    }
+}
+
+#[derive(Bundle)]
+struct HazardBundle {
+    hazard: Hazard,
+    position: Position,
+    previous_position: PreviousPosition,
+    themed: Themed,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+// Hazards are moved by the snake fixed timestep set, see `SnakePlugin`
+pub struct HazardPlugin;
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(spawn_hazards));
+    }
+}
+
+fn spawn_hazards(mut commands: Commands, level: Res<CurrentLevel>, theme: Res<Theme>) {
+    for path in level.0.hazards.iter() {
+        let cells = path.cells();
+        if cells.is_empty() {
+            continue;
+        }
+        commands.spawn_bundle(HazardBundle {
+            position: cells[0],
+            previous_position: PreviousPosition(cells[0]),
+            hazard: Hazard::new(cells, path.mode),
+            themed: Themed(Palette::Hazard),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: theme.hazard,
+                    custom_size: Some(Vec2::new(0.85, 0.85)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                ..Default::default()
+            },
+        });
+    }
+}
+
+pub fn move_hazards(mut query: Query<(&mut Hazard, &mut Position)>) {
+    for (mut hazard, mut pos) in query.iter_mut() {
+        *pos = hazard.step();
+    }
+}
+
+pub fn reset_hazards(mut query: Query<(&mut Hazard, &mut Position)>) {
+    for (mut hazard, mut pos) in query.iter_mut() {
+        *pos = hazard.reset();
+    }
+}
@@ -0,0 +1,210 @@
+use super::arena::Position;
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_LEVEL: &str = include_str!("../../assets/levels/default.ron");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PathMode {
+    BackAndForth,
+    Loop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HazardPath {
+    pub waypoints: Vec<Position>,
+    pub mode: PathMode,
+}
+impl HazardPath {
+    // Expand the waypoints into every cell walked, one cell per tick.
+    // Waypoints that are not in a straight line walk the x axis first.
+    pub fn cells(&self) -> Vec<Position> {
+        let mut waypoints = self.waypoints.clone();
+        if self.mode == PathMode::Loop {
+            if let Some(first) = self.waypoints.first() {
+                waypoints.push(*first);
+            }
+        }
+        let mut current = match waypoints.first() {
+            Some(pos) => *pos,
+            None => return Vec::new(),
+        };
+        let mut cells = vec![current];
+        for target in waypoints.iter().skip(1) {
+            while current != *target {
+                if current.x != target.x {
+                    current.x += (target.x - current.x).signum();
+                } else {
+                    current.y += (target.y - current.y).signum();
+                }
+                cells.push(current);
+            }
+        }
+        // The loop ends where it started
+        if self.mode == PathMode::Loop && cells.len() > 1 {
+            cells.pop();
+        }
+        cells
+    }
+}
+
+// What must be done to clear a campaign level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Goal {
+    EatFood(usize),
+    ReachLength(usize),
+    SurviveSeconds(f32),
+    CollectKeys,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Level {
+    #[serde(default)]
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub goal: Option<Goal>,
+    #[serde(default)]
+    pub spawn: Option<Position>,
+    #[serde(default)]
+    pub walls: Vec<Position>,
+    #[serde(default)]
+    pub food: Vec<Position>,
+    #[serde(default)]
+    pub keys: Vec<Position>,
+    #[serde(default)]
+    pub portals: Vec<(Position, Position)>,
+    #[serde(default)]
+    pub hazards: Vec<HazardPath>,
+}
+impl Level {
+    pub fn from_ron(source: &str) -> Result<Level, ron::Error> {
+        ron::from_str(source)
+    }
+
+    // The default level on the arena size from the config
+    pub fn classic(config: &Config) -> Level {
+        Level::default().resized(config.arena_width, config.arena_height)
+    }
+
+    // Where the head starts, the body trails to its left
+    pub fn spawn_point(&self) -> Position {
+        self.spawn.unwrap_or(Position {
+            x: self.width / 2,
+            y: self.height / 2 - 1,
+        })
+    }
+
+    // Every cell of a snake this long when it spawns, the head first. Parts
+    // that don't fit pile up at the edge and unfold like grown ones.
+    pub fn starting_body(&self, length: usize) -> Vec<Position> {
+        let spawn_point = self.spawn_point();
+        (0..length)
+            .map(|order| Position {
+                x: (spawn_point.x - order as i32).max(0),
+                y: spawn_point.y,
+            })
+            .collect()
+    }
+
+    // The same level on another size, whatever no longer fits is left out
+    pub fn resized(mut self, width: i32, height: i32) -> Level {
+        let fits = |pos: &Position| pos.x < width && pos.y < height;
+        self.width = width;
+        self.height = height;
+        self.spawn = self.spawn.filter(fits);
+        self.walls.retain(fits);
+        self.food.retain(fits);
+        self.keys.retain(fits);
+        self.portals
+            .retain(|(entrance, exit)| fits(entrance) && fits(exit));
+        self.hazards
+            .retain(|hazard| hazard.waypoints.iter().all(fits));
+        self
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level::from_ron(DEFAULT_LEVEL).expect("Bundled default level is malformed")
+    }
+}
+
+// Level used the next time the arena is built
+pub struct CurrentLevel(pub Level);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snakes_spawn_inside_any_arena() {
+        for width in (5..=200).step_by(3).chain([200]) {
+            for height in (5..=150).step_by(3).chain([150]) {
+                let level = Level::default().resized(width, height);
+                // As long as the config lets a snake start
+                for length in 2..=20 {
+                    for pos in level.starting_body(length) {
+                        assert!(
+                            pos.x >= 0 && pos.y >= 0 && pos.x < width && pos.y < height,
+                            "{:?} of a {} long snake on {}x{}",
+                            pos,
+                            length,
+                            width,
+                            height
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resizing_drops_what_no_longer_fits() {
+        let at = |x, y| Position { x, y };
+        let level = Level {
+            spawn: Some(at(12, 3)),
+            walls: vec![at(0, 0), at(9, 9), at(10, 0), at(0, 10)],
+            food: vec![at(3, 3), at(11, 3)],
+            keys: vec![at(3, 12)],
+            portals: vec![
+                (at(1, 1), at(8, 8)),
+                (at(1, 1), at(10, 2)),
+                (at(20, 1), at(2, 2)),
+            ],
+            hazards: vec![
+                HazardPath {
+                    waypoints: vec![at(0, 5), at(9, 5)],
+                    mode: PathMode::BackAndForth,
+                },
+                HazardPath {
+                    waypoints: vec![at(2, 2), at(2, 12)],
+                    mode: PathMode::Loop,
+                },
+            ],
+            ..Level::default()
+        }
+        .resized(10, 10);
+        assert_eq!((level.width, level.height), (10, 10));
+        assert_eq!(level.spawn, None);
+        assert_eq!(level.walls, [at(0, 0), at(9, 9)]);
+        assert_eq!(level.food, [at(3, 3)]);
+        assert!(level.keys.is_empty());
+        assert_eq!(level.portals, [(at(1, 1), at(8, 8))]);
+        assert_eq!(level.hazards.len(), 1);
+        assert_eq!(level.hazards[0].waypoints, [at(0, 5), at(9, 5)]);
+        // Every cell walked between the waypoints is inside as well
+        assert!(level.hazards[0]
+            .cells()
+            .iter()
+            .all(|pos| pos.x < 10 && pos.y < 10));
+    }
+
+    #[test]
+    fn the_bundled_level_shrinks_to_the_smallest_arena() {
+        let level = Level::default().resized(5, 5);
+        assert!(level.portals.is_empty());
+        assert!(level.hazards.is_empty());
+    }
+}
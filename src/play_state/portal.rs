@@ -0,0 +1,48 @@
+use super::{arena::Position, level::CurrentLevel, AppState};
+use crate::theme::{Palette, Theme, Themed};
+use bevy::prelude::*;
+
+#[derive(Component, Debug)]
+pub struct Portal {
+    pub exit: Position,
+}
+
+#[derive(Bundle)]
+struct PortalBundle {
+    portal: Portal,
+    position: Position,
+    themed: Themed,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+pub struct PortalPlugin;
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(spawn_portals));
+    }
+}
+
+fn spawn_portals(mut commands: Commands, level: Res<CurrentLevel>, theme: Res<Theme>) {
+    let mut spawn_portal = |position: Position, exit: Position| {
+        commands.spawn_bundle(PortalBundle {
+            portal: Portal { exit },
+            position,
+            themed: Themed(Palette::Portal),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: theme.portal,
+                    custom_size: Some(Vec2::ONE),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 5.0),
+                ..Default::default()
+            },
+        });
+    };
+    // Entering one portal of a pair exits from the other one
+    for (a, b) in level.0.portals.iter() {
+        spawn_portal(*a, *b);
+        spawn_portal(*b, *a);
+    }
+}
@@ -0,0 +1,164 @@
+use super::{
+    level::{CurrentLevel, Level},
+    snake::{Direction, Head, MovementStatus, TurnEvent},
+    AppState, GameMode, GameRng, Rules,
+};
+use crate::config::Config;
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Every run is saved here when it ends, the one before is overwritten
+pub const REPLAY_FILE: &str = "last_replay.ron";
+
+// All a run needs to play out again: the food comes from the seed and the
+// snake turns on the same ticks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub mode: GameMode,
+    pub level: Level,
+    pub tick_step: f64,
+    pub starting_length: usize,
+    pub tail_cut: bool,
+    // Ticks the run lasted
+    pub ticks: usize,
+    // Where the snake turned to, on which tick
+    pub turns: Vec<(usize, Direction)>,
+}
+
+// A replay waiting for the next run to play it back, see `cli`. The player's
+// own config and rules are put back once it has played.
+pub struct PlayBack {
+    pub replay: Replay,
+    pub config: Config,
+    pub rules: Rules,
+}
+
+// The run being played, written down as it goes. A run that is played back
+// follows its replay instead and isn't saved again.
+pub struct Recording {
+    replay: Replay,
+    tick: usize,
+    // Index of the next turn to play back
+    next_turn: usize,
+    playing_back: bool,
+    // What the played back run replaced, see `PlayBack`
+    player: Option<(Config, Rules)>,
+}
+impl Recording {
+    pub fn is_playing_back(&self) -> bool {
+        self.playing_back
+    }
+}
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(start_recording))
+            .add_system_set(SystemSet::on_exit(AppState::PlayState).with_system(save_recording));
+    }
+}
+
+fn start_recording(
+    mut commands: Commands,
+    play_back: Option<Res<PlayBack>>,
+    rng: Res<GameRng>,
+    game_mode: Res<GameMode>,
+    level: Res<CurrentLevel>,
+    config: Res<Config>,
+    rules: Res<Rules>,
+) {
+    let (replay, player) = match play_back {
+        Some(play_back) => {
+            commands.remove_resource::<PlayBack>();
+            let player = (play_back.config.clone(), play_back.rules.clone());
+            (play_back.replay.clone(), Some(player))
+        }
+        None => (
+            Replay {
+                seed: rng.seed,
+                mode: *game_mode,
+                level: level.0.clone(),
+                tick_step: config.tick_step,
+                starting_length: config.starting_length,
+                tail_cut: rules.tail_cut,
+                ticks: 0,
+                turns: Vec::new(),
+            },
+            None,
+        ),
+    };
+    commands.insert_resource(Recording {
+        replay,
+        tick: 0,
+        next_turn: 0,
+        playing_back: player.is_some(),
+        player,
+    });
+}
+
+// Runs on the tick before the snake moves
+pub fn play_back_turns(
+    recording: Option<ResMut<Recording>>,
+    mut query: Query<&mut MovementStatus, With<Head>>,
+) {
+    let mut recording = match recording {
+        Some(recording) if recording.playing_back => recording,
+        _ => return,
+    };
+    let direction = match recording.replay.turns.get(recording.next_turn) {
+        Some((tick, direction)) if *tick == recording.tick => *direction,
+        _ => return,
+    };
+    recording.next_turn += 1;
+    for mut movement_status in query.iter_mut() {
+        movement_status.next_direction = direction;
+        movement_status.buffer_direction = None;
+    }
+}
+
+// Runs on the tick after everything else. A run played back ends on the tick
+// its recording did, unless it ended there by itself already.
+pub fn record_turns(
+    recording: Option<ResMut<Recording>>,
+    mut turn_reader: EventReader<TurnEvent>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    let mut recording = match recording {
+        Some(recording) => recording,
+        None => return,
+    };
+    let tick = recording.tick;
+    if !recording.playing_back {
+        for turn in turn_reader.iter() {
+            recording.replay.turns.push((tick, turn.0));
+        }
+    }
+    recording.tick += 1;
+    if recording.playing_back && recording.tick >= recording.replay.ticks {
+        let _ = app_state.set(AppState::MainMenuState);
+    }
+}
+
+fn save_recording(
+    mut commands: Commands,
+    recording: Option<ResMut<Recording>>,
+    mut config: ResMut<Config>,
+    mut rules: ResMut<Rules>,
+) {
+    let mut recording = match recording {
+        Some(recording) => recording,
+        None => return,
+    };
+    if !recording.playing_back && recording.tick > 0 {
+        recording.replay.ticks = recording.tick;
+        storage::save(REPLAY_FILE, &recording.replay);
+    }
+    // The replay's speed, length and rules were only for its own run
+    if let Some((player_config, player_rules)) = recording.player.take() {
+        *config = player_config;
+        *rules = player_rules;
+    }
+    commands.remove_resource::<Recording>();
+}
@@ -1,9 +1,13 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, render::camera::Camera};
 
 use super::snake::AmountBodyParts;
 use super::AppState;
+use crate::config::Config;
+use crate::loading_state::GameAssets;
+use crate::theme::{Palette, Theme, Themed};
 
-const SCORE_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+// Behind the snake and the food
+const SCORE_Z: f32 = 0.0;
 
 #[derive(Component, Debug)]
 struct ScoreText;
@@ -11,6 +15,7 @@ struct ScoreText;
 #[derive(Bundle)]
 struct ScoreTextBundle {
     lable: ScoreText,
+    themed: Themed,
     #[bundle]
     text: Text2dBundle,
 }
@@ -19,28 +24,48 @@ pub struct ScoreBoardPlugin;
 impl Plugin for ScoreBoardPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(spawn_scoreboard))
-            .add_system_set(SystemSet::on_update(AppState::PlayState).with_system(change_score));
+            .add_system_set(SystemSet::on_update(AppState::PlayState).with_system(change_score))
+            .add_system_set(
+                SystemSet::on_exit(AppState::PlayState).with_system(destroy_scoreboard),
+            );
     }
 }
 
-fn spawn_scoreboard(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("Open_Sans/OpenSans-ExtraBold.ttf");
+// A child of the camera, so it stays in the middle of the view while the
+// camera follows the head
+fn spawn_scoreboard(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    config: Res<Config>,
+    theme: Res<Theme>,
+    camera_query: Query<(Entity, &Transform), With<Camera>>,
+) {
+    let (camera, camera_transform) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No camera to put the score on"),
+    };
+    let font = game_assets.font.clone();
     let text_style = TextStyle {
         font,
-        font_size: 150.0,
-        color: SCORE_COLOR,
+        font_size: 150.0 * config.text_scale,
+        color: theme.score,
     };
     let text_alignment = TextAlignment {
         vertical: VerticalAlign::Center,
         horizontal: HorizontalAlign::Center,
     };
-    commands.spawn_bundle(ScoreTextBundle {
-        lable: ScoreText,
-        text: Text2dBundle {
-            text: Text::with_section("0", text_style.clone(), text_alignment),
-            ..Default::default()
-        },
-    });
+    let score = commands
+        .spawn_bundle(ScoreTextBundle {
+            lable: ScoreText,
+            themed: Themed(Palette::Score),
+            text: Text2dBundle {
+                text: Text::with_section("0", text_style.clone(), text_alignment),
+                transform: Transform::from_xyz(0.0, 0.0, SCORE_Z - camera_transform.translation.z),
+                ..Default::default()
+            },
+        })
+        .id();
+    commands.entity(camera).add_child(score);
 }
 
 fn change_score(
@@ -49,8 +74,14 @@ fn change_score(
 ) {
     match query.get_single_mut() {
         Ok(mut text) => {
-            text.sections[0].value = format!("{}", amount_body_parts.0 - 1);
+            text.sections[0].value = format!("{}", amount_body_parts.score());
         }
         Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No body error"),
     }
 }
+
+fn destroy_scoreboard(mut commands: Commands, query: Query<Entity, With<ScoreText>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
@@ -0,0 +1,164 @@
+use super::{
+    arena::Position,
+    snake::{BodyPart, Head, Order},
+    AppState,
+};
+use crate::loading_state::GameAssets;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+// Atlases in assets/skins, a row of head, tail, straight and corner pieces
+pub const SKINS: [&str; 3] = ["flat", "scales", "ribbon"];
+pub const SKIN_FILE: &str = "skin.ron";
+const TILE_SIZE: f32 = 32.0;
+
+// Index of each piece in the atlas. Every piece is drawn going right: the head
+// faces right, the tail and the straight piece connect to the right and the
+// corner connects left and down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece {
+    Head = 0,
+    Tail = 1,
+    Straight = 2,
+    Corner = 3,
+}
+
+// Which skin is picked, saved between sessions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkinChoice {
+    pub name: String,
+}
+impl Default for SkinChoice {
+    fn default() -> Self {
+        SkinChoice {
+            name: SKINS[0].to_string(),
+        }
+    }
+}
+impl SkinChoice {
+    fn index(&self) -> usize {
+        SKINS
+            .iter()
+            .position(|name| *name == self.name)
+            .unwrap_or(0)
+    }
+
+    pub fn next(&mut self) {
+        self.name = SKINS[(self.index() + 1) % SKINS.len()].to_string();
+    }
+}
+
+pub struct SkinAtlases(Vec<Handle<TextureAtlas>>);
+impl SkinAtlases {
+    pub fn get(&self, choice: &SkinChoice) -> Handle<TextureAtlas> {
+        self.0[choice.index()].clone()
+    }
+}
+
+pub struct SkinPlugin;
+impl Plugin for SkinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_skins)
+            .add_system_set(SystemSet::on_update(AppState::PlayState).with_system(update_skin));
+    }
+}
+
+fn load_skins(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let handles = game_assets
+        .skins
+        .iter()
+        .map(|image| {
+            atlases.add(TextureAtlas::from_grid(
+                image.clone(),
+                Vec2::splat(TILE_SIZE),
+                4,
+                1,
+            ))
+        })
+        .collect();
+    commands.insert_resource(SkinAtlases(handles));
+}
+
+// Quarter turns from right to go from one cell to its neighbour, counter
+// clockwise as drawn. Cells that are not neighbours are split by a portal.
+fn quarter(from: Position, to: Position) -> Option<u8> {
+    match (to.x - from.x, to.y - from.y) {
+        (1, 0) => Some(0),
+        (0, -1) => Some(1),
+        (-1, 0) => Some(2),
+        (0, 1) => Some(3),
+        _ => None,
+    }
+}
+
+// Picks a piece from the directions to the parts in front and behind
+fn piece(front: Option<u8>, back: Option<u8>, is_head: bool, is_tail: bool) -> Option<(Piece, u8)> {
+    if is_head {
+        return back.map(|back| (Piece::Head, (back + 2) % 4));
+    }
+    if is_tail {
+        return front.map(|front| (Piece::Tail, front));
+    }
+    match (front, back) {
+        (Some(front), Some(back)) if (front + 2) % 4 == back => Some((Piece::Straight, front)),
+        // The drawn corner goes from left to down, a quarter turn apart
+        (Some(front), Some(back)) => {
+            let first = if (front + 1) % 4 == back { front } else { back };
+            Some((Piece::Corner, (first + 2) % 4))
+        }
+        // Next to a portal the part is drawn as if it went straight through
+        (Some(front), None) => Some((Piece::Straight, front)),
+        (None, Some(back)) => Some((Piece::Straight, (back + 2) % 4)),
+        (None, None) => None,
+    }
+}
+
+fn update_skin(
+    head_query: Query<&Position, With<Head>>,
+    body_query: Query<(&Position, &Order), With<BodyPart>>,
+    mut sprite_query: Query<
+        (
+            &Position,
+            Option<&Order>,
+            &mut TextureAtlasSprite,
+            &mut Transform,
+        ),
+        Or<(With<Head>, With<BodyPart>)>,
+    >,
+) {
+    // Cells of the snake from head to tail
+    let length = body_query
+        .iter()
+        .map(|(_, order)| order.0)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut cells: Vec<Option<Position>> = vec![None; length];
+    if let Ok(head_pos) = head_query.get_single() {
+        cells[0] = Some(*head_pos);
+    }
+    for (pos, order) in body_query.iter() {
+        cells[order.0] = Some(*pos);
+    }
+
+    for (pos, order, mut sprite, mut transform) in sprite_query.iter_mut() {
+        let index = order.map_or(0, |order| order.0);
+        let neighbour = |other: Option<usize>| {
+            other
+                .and_then(|other| cells.get(other).copied().flatten())
+                .and_then(|other| quarter(*pos, other))
+        };
+        let front = neighbour(index.checked_sub(1));
+        let back = neighbour(Some(index + 1));
+        // A part without a known direction keeps how it was drawn
+        if let Some((piece, turns)) = piece(front, back, index == 0, index == length - 1) {
+            sprite.index = piece as usize;
+            transform.rotation = Quat::from_rotation_z(turns as f32 * FRAC_PI_2);
+        }
+    }
+}
@@ -1,57 +1,82 @@
-use super::arena::Position;
+use super::achievements::track_achievements;
+use super::arena::{store_previous_positions, ArenaSize, Position, PreviousPosition};
+use super::campaign::{check_goal, collect_keys, reset_goal};
 use super::food::spawn_food;
+use super::hazard::{move_hazards, reset_hazards, Hazard};
 use super::is_in_play_state_chain;
-use super::{
-    arena::{ARENA_HEIGHT, ARENA_WIDTH, CEL_SIZE},
-    food::Food,
-    AppState,
-};
-use bevy::{core::FixedTimestep, ecs::schedule::ShouldRun, prelude::*};
+use super::level::CurrentLevel;
+use super::portal::Portal;
+use super::replay::{play_back_turns, record_turns, Recording};
+use super::skin::{SkinAtlases, SkinChoice};
+use super::stats::track_stats;
+use super::survival::{reset_shrink, shrink_arena};
+use super::time_attack::tick_clock;
+use super::wall::Wall;
+use super::{food::Food, AppState, Rules};
+use crate::config::Config;
+use crate::theme::{Palette, Theme, Themed};
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use serde::{Deserialize, Serialize};
 
-// Snake constant
-const HEAD_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
-const BODY_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+// Seconds a cut off tail takes to fade away
+const DEBRIS_FADE: f32 = 1.5;
 
 // Components and Bundles definitions
 #[derive(Component, Debug)]
-struct Head;
+pub struct Head;
 
 #[derive(Bundle)]
 struct HeadBundle {
     head: Head,
     position: Position,
+    previous_position: PreviousPosition,
     movement_status: MovementStatus,
+    themed: Themed,
     #[bundle]
-    sprite: SpriteBundle,
+    sprite: SpriteSheetBundle,
 }
 
 #[derive(Component, Debug)]
-struct BodyPart;
+pub struct BodyPart;
 
+// Place in the body counting from the head, the first part is 1
 #[derive(Component, Debug)]
-struct Order(usize);
+pub struct Order(pub usize);
 
+// Body parts behind the head, and how many it spawned with
 #[derive(Debug)]
-pub struct AmountBodyParts(pub usize);
+pub struct AmountBodyParts(pub usize, pub usize);
+impl AmountBodyParts {
+    // Every part grown since spawning is a point
+    pub fn score(&self) -> usize {
+        self.0.saturating_sub(self.1)
+    }
+}
+
+// Body parts cut off by the tail cut rule
+#[derive(Component, Debug)]
+struct Debris(Timer);
 
 #[derive(Bundle)]
 struct BodyPartBundle {
     body_part: BodyPart,
     position: Position,
+    previous_position: PreviousPosition,
     order: Order,
+    themed: Themed,
     #[bundle]
-    sprite: SpriteBundle,
+    sprite: SpriteSheetBundle,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Direction {
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Direction {
     Left,
     Right,
     Up,
     Down,
 }
 impl Direction {
-    fn opposite(&self) -> Direction {
+    pub fn opposite(&self) -> Direction {
         match self {
             Direction::Left => Direction::Right,
             Direction::Right => Direction::Left,
@@ -72,16 +97,48 @@ impl Into<Position> for Direction {
 }
 
 #[derive(Component, Debug)]
-struct MovementStatus {
+pub struct MovementStatus {
     current_direction: Direction,
-    next_direction: Direction,
-    buffer_direction: Option<Direction>,
+    pub next_direction: Direction,
+    pub buffer_direction: Option<Direction>,
+    // Direction of the last step taken
+    moved_direction: Direction,
 }
 
 // Events definitions
-struct EatEvent;
+pub struct EatEvent;
+
+// What ended the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DeathCause {
+    Boundary,
+    Body,
+    Wall,
+    Hazard,
+    ClosingWall,
+}
 
-struct GameOver(usize);
+pub struct GameOver(pub usize, pub DeathCause);
+
+// The direction of the step that turned
+pub struct TurnEvent(pub Direction);
+
+// A turn asked for by something other than the arrow keys, see `touch`
+pub struct SteerEvent(pub Direction);
+
+// Time since the last tick, like bevy's `FixedTimestep` but the step is read
+// from the config every frame so a replay can tick at its own pace
+#[derive(Debug, Default)]
+pub struct SnakeTick {
+    accumulator: f64,
+    looping: bool,
+}
+impl SnakeTick {
+    // How far into the next tick we are, drawing slides between cells by it
+    pub fn overstep_percentage(&self, tick_step: f64) -> f64 {
+        self.accumulator / tick_step
+    }
+}
 
 // Plugin definition
 pub struct SnakePlugin;
@@ -89,6 +146,9 @@ impl Plugin for SnakePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<EatEvent>();
         app.add_event::<GameOver>();
+        app.add_event::<TurnEvent>();
+        app.add_event::<SteerEvent>();
+        app.init_resource::<SnakeTick>();
 
         app.add_system_set(
             SystemSet::on_enter(AppState::PlayState)
@@ -96,23 +156,94 @@ impl Plugin for SnakePlugin {
                 .with_system(spawn_body),
         )
         .add_system_set(
-            SystemSet::on_update(AppState::PlayState).with_system(
-                handle_input
-                    .label(SnakeStages::Input)
-                    .before(SnakeStages::Movement),
-            ),
+            SystemSet::on_update(AppState::PlayState)
+                .with_system(
+                    handle_input
+                        .label(SnakeStages::Input)
+                        .before(SnakeStages::Movement),
+                )
+                .with_system(fade_debris),
         )
         .add_system_set(
             SystemSet::new()
                 .label(GameStages::Update)
-                .with_run_criteria(FixedTimestep::step(0.125).chain(is_in_play_state_chain))
-                .with_system(movement.label(SnakeStages::Movement))
-                .with_system(eat.label(SnakeStages::Eat).after(SnakeStages::Movement))
-                .with_system(grow.label(SnakeStages::Grow).after(SnakeStages::Eat))
+                .with_run_criteria(snake_tick.chain(is_in_play_state_chain))
+                .with_system(store_previous_positions.label(SnakeStages::Previous))
+                .with_system(
+                    play_back_turns
+                        .label(SnakeStages::PlayBack)
+                        .before(SnakeStages::Movement),
+                )
+                .with_system(
+                    movement
+                        .label(SnakeStages::Movement)
+                        .after(SnakeStages::Previous),
+                )
+                .with_system(
+                    teleport
+                        .label(SnakeStages::Teleport)
+                        .after(SnakeStages::Movement),
+                )
+                .with_system(
+                    collect_keys
+                        .label(SnakeStages::Keys)
+                        .after(SnakeStages::Teleport),
+                )
+                .with_system(eat.label(SnakeStages::Eat).after(SnakeStages::Teleport))
+                // Growing after collision keeps the orders right when the tail is cut
+                .with_system(
+                    grow.label(SnakeStages::Grow)
+                        .after(SnakeStages::Eat)
+                        .after(SnakeStages::Collision),
+                )
+                // Hazards move after the snake so collision sees both final positions
+                .with_system(
+                    move_hazards
+                        .label(SnakeStages::Hazards)
+                        .after(SnakeStages::Teleport),
+                )
                 .with_system(
                     collision
                         .label(SnakeStages::Collision)
-                        .after(SnakeStages::Movement),
+                        .after(SnakeStages::Hazards),
+                )
+                .with_system(
+                    check_goal
+                        .label(SnakeStages::Goal)
+                        .after(SnakeStages::Keys)
+                        .after(SnakeStages::Grow)
+                        .after(SnakeStages::Collision),
+                )
+                .with_system(
+                    tick_clock
+                        .label(SnakeStages::Clock)
+                        .after(SnakeStages::Eat)
+                        .after(SnakeStages::Collision),
+                )
+                .with_system(
+                    shrink_arena
+                        .label(SnakeStages::Shrink)
+                        .after(SnakeStages::Collision),
+                )
+                .with_system(
+                    track_achievements
+                        .label(SnakeStages::Achievements)
+                        .after(SnakeStages::Grow)
+                        .after(SnakeStages::Shrink),
+                )
+                .with_system(
+                    track_stats
+                        .label(SnakeStages::Stats)
+                        .after(SnakeStages::Grow)
+                        .after(SnakeStages::Shrink),
+                )
+                // Last, so a run that ends by itself on this tick still does
+                .with_system(
+                    record_turns
+                        .label(SnakeStages::Record)
+                        .after(SnakeStages::Movement)
+                        .after(SnakeStages::Goal)
+                        .after(SnakeStages::Clock),
                 ),
         )
         .add_system_set(
@@ -135,7 +266,14 @@ impl Plugin for SnakePlugin {
                     spawn_food
                         .label(GameOverStages::RespawnFood)
                         .after(GameOverStages::Clear),
-                ),
+                )
+                .with_system(
+                    reset_hazards
+                        .label(GameOverStages::ResetHazards)
+                        .before(GameOverStages::RespawnFood),
+                )
+                .with_system(reset_goal.label(GameOverStages::ResetGoal))
+                .with_system(reset_shrink.label(GameOverStages::ResetShrink)),
         );
     }
 }
@@ -150,10 +288,21 @@ pub enum GameStages {
 #[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum SnakeStages {
     Input,
+    Previous,
     Movement,
+    Teleport,
+    Keys,
     Eat,
     Grow,
+    Hazards,
     Collision,
+    Goal,
+    Clock,
+    Shrink,
+    Achievements,
+    Stats,
+    PlayBack,
+    Record,
 }
 
 #[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
@@ -162,47 +311,87 @@ pub enum GameOverStages {
     RespawnBody,
     RespawnHead,
     RespawnFood,
+    ResetHazards,
+    ResetGoal,
+    ResetShrink,
 }
 
-fn spawn_head(mut commands: Commands) {
+fn spawn_head(
+    mut commands: Commands,
+    level: Res<CurrentLevel>,
+    theme: Res<Theme>,
+    skin_atlases: Res<SkinAtlases>,
+    skin_choice: Res<SkinChoice>,
+) {
     commands.spawn_bundle(HeadBundle {
         head: Head,
-        position: Position { x: 20, y: 14 },
+        position: level.0.spawn_point(),
+        previous_position: PreviousPosition(level.0.spawn_point()),
         movement_status: MovementStatus {
             current_direction: Direction::Right,
             next_direction: Direction::Right,
             buffer_direction: None,
+            moved_direction: Direction::Right,
         },
-        sprite: SpriteBundle {
-            sprite: Sprite {
-                color: HEAD_COLOR,
-                custom_size: Some(Vec2::new(CEL_SIZE * 0.95, CEL_SIZE * 0.95)),
+        themed: Themed(Palette::Head),
+        sprite: SpriteSheetBundle {
+            sprite: TextureAtlasSprite {
+                index: 0,
+                color: theme.head,
+                custom_size: Some(Vec2::ONE),
                 ..Default::default()
             },
+            texture_atlas: skin_atlases.get(&skin_choice),
+            transform: Transform::from_xyz(0.0, 0.0, 10.0),
             ..Default::default()
         },
     });
 }
 
-fn spawn_body(mut commands: Commands) {
-    commands.spawn_bundle(BodyPartBundle {
-        body_part: BodyPart,
-        position: Position { x: 19, y: 14 },
-        order: Order(1),
-        sprite: SpriteBundle {
-            sprite: Sprite {
-                color: BODY_COLOR,
-                custom_size: Some(Vec2::new(CEL_SIZE * 0.75, CEL_SIZE * 0.75)),
+fn spawn_body(
+    mut commands: Commands,
+    config: Res<Config>,
+    level: Res<CurrentLevel>,
+    theme: Res<Theme>,
+    skin_atlases: Res<SkinAtlases>,
+    skin_choice: Res<SkinChoice>,
+) {
+    let amount = config.starting_length - 1;
+    let starting_body = level.0.starting_body(config.starting_length);
+    for (order, position) in starting_body.into_iter().enumerate().skip(1) {
+        commands.spawn_bundle(BodyPartBundle {
+            body_part: BodyPart,
+            position,
+            previous_position: PreviousPosition(position),
+            order: Order(order),
+            themed: Themed(Palette::Body),
+            sprite: SpriteSheetBundle {
+                sprite: TextureAtlasSprite {
+                    index: 1,
+                    color: theme.body,
+                    custom_size: Some(Vec2::ONE),
+                    ..Default::default()
+                },
+                texture_atlas: skin_atlases.get(&skin_choice),
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
                 ..Default::default()
             },
-            ..Default::default()
-        },
-    });
-    commands.insert_resource(AmountBodyParts(1));
+        });
+    }
+    commands.insert_resource(AmountBodyParts(amount, amount));
 }
 
 // This is buggy as hell TODO fix
-fn handle_input(mut query: Query<&mut MovementStatus, With<Head>>, key_input: Res<Input<KeyCode>>) {
+fn handle_input(
+    mut query: Query<&mut MovementStatus, With<Head>>,
+    key_input: Res<Input<KeyCode>>,
+    mut steer_reader: EventReader<SteerEvent>,
+    recording: Option<Res<Recording>>,
+) {
+    // A replay steers by itself
+    if matches!(recording.as_deref(), Some(recording) if recording.is_playing_back()) {
+        return;
+    }
     let mut target_direction: Vec<Direction> = Vec::new();
     if key_input.pressed(KeyCode::Left) {
         target_direction.push(Direction::Left);
@@ -216,6 +405,7 @@ fn handle_input(mut query: Query<&mut MovementStatus, With<Head>>, key_input: Re
     if key_input.pressed(KeyCode::Down) {
         target_direction.push(Direction::Down);
     }
+    target_direction.extend(steer_reader.iter().map(|steer| steer.0));
 
     for td in target_direction.iter() {
         for mut movement_status in query.iter_mut() {
@@ -233,6 +423,7 @@ fn handle_input(mut query: Query<&mut MovementStatus, With<Head>>, key_input: Re
 
 // This is buggy as hell TODO fix
 fn movement(
+    mut event_writer: EventWriter<TurnEvent>,
     amount_body_parts: Res<AmountBodyParts>,
     mut query: QuerySet<(
         QueryState<(&mut MovementStatus, &mut Position), With<Head>>,
@@ -246,6 +437,10 @@ fn movement(
             head_prev_pos = *head_pos;
             // Move head
             let step_direction: Position = head_ms.next_direction.into();
+            if head_ms.next_direction != head_ms.moved_direction {
+                head_ms.moved_direction = head_ms.next_direction;
+                event_writer.send(TurnEvent(head_ms.next_direction));
+            }
             if let Some(d) = head_ms.buffer_direction {
                 head_ms.next_direction = d;
                 head_ms.buffer_direction = None;
@@ -268,6 +463,24 @@ fn movement(
     }
 }
 
+// The body needs no special handling, every part takes the previous
+// position of the one in front so it follows the head through the portal
+fn teleport(
+    mut head_query: Query<&mut Position, With<Head>>,
+    portal_query: Query<(&Position, &Portal), Without<Head>>,
+) {
+    let mut head_pos = match head_query.get_single_mut() {
+        Ok(pos) => pos,
+        Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No head to teleport"),
+    };
+    for (portal_pos, portal) in portal_query.iter() {
+        if *portal_pos == *head_pos {
+            *head_pos = portal.exit;
+            return;
+        }
+    }
+}
+
 fn eat(
     mut commands: Commands,
     mut event_writer: EventWriter<EatEvent>,
@@ -278,21 +491,21 @@ fn eat(
         Ok(pos) => pos,
         Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No head for food"),
     };
-    match food_query.get_single() {
-        Ok((ent, pos)) => {
-            if pos == head_pos {
-                commands.entity(ent).despawn();
-                event_writer.send(EatEvent);
-            }
+    for (ent, pos) in food_query.iter() {
+        if pos == head_pos {
+            commands.entity(ent).despawn();
+            event_writer.send(EatEvent);
         }
-        Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No food for head"),
-    };
+    }
 }
 
 fn grow(
     mut commands: Commands,
     mut event_reader: EventReader<EatEvent>,
     mut amount_body_parts: ResMut<AmountBodyParts>,
+    theme: Res<Theme>,
+    skin_atlases: Res<SkinAtlases>,
+    skin_choice: Res<SkinChoice>,
     body_query: Query<(&Position, &Order), With<BodyPart>>,
 ) {
     if event_reader.iter().next().is_none() {
@@ -304,13 +517,18 @@ fn grow(
             commands.spawn_bundle(BodyPartBundle {
                 body_part: BodyPart,
                 position: *pos,
+                previous_position: PreviousPosition(*pos),
                 order: Order(amount_body_parts.0),
-                sprite: SpriteBundle {
-                    sprite: Sprite {
-                        color: BODY_COLOR,
-                        custom_size: Some(Vec2::new(CEL_SIZE * 0.75, CEL_SIZE * 0.75)),
+                themed: Themed(Palette::Body),
+                sprite: SpriteSheetBundle {
+                    sprite: TextureAtlasSprite {
+                        index: 1,
+                        color: theme.body,
+                        custom_size: Some(Vec2::ONE),
                         ..Default::default()
                     },
+                    texture_atlas: skin_atlases.get(&skin_choice),
+                    transform: Transform::from_xyz(0.0, 0.0, 10.0),
                     ..Default::default()
                 },
             });
@@ -319,28 +537,102 @@ fn grow(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn collision(
+    mut commands: Commands,
     mut event_writer: EventWriter<GameOver>,
-    amount_body_parts: Res<AmountBodyParts>,
-    body_query: Query<&Position, With<BodyPart>>,
+    mut amount_body_parts: ResMut<AmountBodyParts>,
+    rules: Res<Rules>,
+    arena_size: Res<ArenaSize>,
+    body_query: Query<(Entity, &Position, &Order), With<BodyPart>>,
     head_query: Query<&Position, With<Head>>,
+    hazard_query: Query<(&Position, &Hazard)>,
+    wall_query: Query<&Position, With<Wall>>,
 ) {
     let head_pos = match head_query.get_single() {
         Ok(pos) => pos,
         Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No head to collide"),
     };
-    if head_pos.x < 0 || head_pos.y < 0 || head_pos.x >= ARENA_WIDTH || head_pos.y >= ARENA_HEIGHT {
-        event_writer.send(GameOver(amount_body_parts.0 - 1));
+    if !arena_size.contains(head_pos) {
+        event_writer.send(GameOver(amount_body_parts.score(), DeathCause::Boundary));
         return;
     }
-    for body_pos in body_query.iter() {
-        if head_pos == body_pos {
-            event_writer.send(GameOver(amount_body_parts.0 - 1));
+    let bitten = body_query
+        .iter()
+        .find(|(_, pos, _)| *pos == head_pos)
+        .map(|(_, _, order)| order.0);
+    match bitten {
+        // Biting the first part would leave no body, so it is always deadly
+        Some(bitten) if rules.tail_cut && bitten > 1 => {
+            for (ent, _, order) in body_query.iter() {
+                if order.0 >= bitten {
+                    commands
+                        .entity(ent)
+                        .remove::<BodyPart>()
+                        .remove::<Order>()
+                        .insert(Debris(Timer::from_seconds(DEBRIS_FADE, false)));
+                }
+            }
+            amount_body_parts.0 = bitten - 1;
+        }
+        Some(_) => {
+            event_writer.send(GameOver(amount_body_parts.score(), DeathCause::Body));
+            return;
+        }
+        None => (),
+    }
+    for wall_pos in wall_query.iter() {
+        if head_pos == wall_pos {
+            event_writer.send(GameOver(amount_body_parts.score(), DeathCause::Wall));
+            return;
+        }
+    }
+    // The first body part is where the head was before moving
+    let neck_pos = body_query
+        .iter()
+        .find(|(_, _, order)| order.0 == 1)
+        .map(|(_, pos, _)| *pos);
+    for (hazard_pos, hazard) in hazard_query.iter() {
+        // Head and hazard swapping cells never share one, but they did cross
+        let crossed = hazard.previous == *head_pos && Some(*hazard_pos) == neck_pos;
+        let touched =
+            hazard_pos == head_pos || body_query.iter().any(|(_, pos, _)| pos == hazard_pos);
+        if crossed || touched {
+            event_writer.send(GameOver(amount_body_parts.score(), DeathCause::Hazard));
             return;
         }
     }
 }
 
+fn fade_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Debris, &mut TextureAtlasSprite)>,
+) {
+    for (ent, mut debris, mut sprite) in query.iter_mut() {
+        debris.0.tick(time.delta());
+        sprite.color.set_a(1.0 - debris.0.percent());
+        if debris.0.finished() {
+            commands.entity(ent).despawn();
+        }
+    }
+}
+
+// Runs as many ticks as fit in the time since the last frame
+fn snake_tick(time: Res<Time>, config: Res<Config>, mut tick: ResMut<SnakeTick>) -> ShouldRun {
+    if !tick.looping {
+        tick.accumulator += time.delta_seconds_f64();
+    }
+    if tick.accumulator >= config.tick_step {
+        tick.accumulator -= config.tick_step;
+        tick.looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        tick.looping = false;
+        ShouldRun::No
+    }
+}
+
 fn game_over(mut event_reader: EventReader<GameOver>) -> ShouldRun {
     if event_reader.iter().next().is_some() {
         ShouldRun::Yes
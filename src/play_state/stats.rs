@@ -0,0 +1,126 @@
+use super::{
+    arena::{ArenaSize, Position},
+    snake::{AmountBodyParts, DeathCause, EatEvent, GameOver, Head, TurnEvent},
+    AppState, GameMode,
+};
+use crate::config::Config;
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const STATS_FILE: &str = "stats.ron";
+
+// Numbers for a single run, a run ends on death or when leaving the arena
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    pub food: usize,
+    pub ticks: usize,
+    // Ticks times the tick step they were played at, set when the run ends
+    #[serde(default)]
+    pub seconds: f64,
+    pub turns: usize,
+    // Cells walked, portal jumps don't count
+    pub distance: usize,
+    pub length: usize,
+    // Ticks spent in each quarter: top left, top right, bottom left, bottom right
+    pub quadrants: [usize; 4],
+    // None when the run was left or ended without dying
+    pub death: Option<DeathCause>,
+}
+
+// Totals over every run ever played, saved on disk
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LifetimeStats {
+    pub runs: usize,
+    pub food: usize,
+    pub ticks: usize,
+    #[serde(default)]
+    pub seconds: f64,
+    pub turns: usize,
+    pub distance: usize,
+    pub best_length: usize,
+    pub quadrants: [usize; 4],
+    pub deaths: BTreeMap<DeathCause, usize>,
+    pub last_run: Option<RunStats>,
+}
+impl LifetimeStats {
+    fn add(&mut self, run: &RunStats) {
+        self.runs += 1;
+        self.food += run.food;
+        self.ticks += run.ticks;
+        self.seconds += run.seconds;
+        self.turns += run.turns;
+        self.distance += run.distance;
+        self.best_length = self.best_length.max(run.length);
+        for (total, ticks) in self.quadrants.iter_mut().zip(run.quadrants.iter()) {
+            *total += ticks;
+        }
+        if let Some(cause) = run.death {
+            *self.deaths.entry(cause).or_insert(0) += 1;
+        }
+        self.last_run = Some(run.clone());
+    }
+}
+
+// Stats are gathered by the snake fixed timestep set, see `SnakePlugin`
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<LifetimeStats>(STATS_FILE))
+            .init_resource::<RunStats>()
+            .add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(reset_run))
+            .add_system_set(SystemSet::on_exit(AppState::PlayState).with_system(finish_run));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn track_stats(
+    mut eat_reader: EventReader<EatEvent>,
+    mut turn_reader: EventReader<TurnEvent>,
+    mut game_over_reader: EventReader<GameOver>,
+    mut run: ResMut<RunStats>,
+    mut lifetime: ResMut<LifetimeStats>,
+    game_mode: Res<GameMode>,
+    arena_size: Res<ArenaSize>,
+    amount_body_parts: Res<AmountBodyParts>,
+    config: Res<Config>,
+    head_query: Query<&Position, With<Head>>,
+) {
+    // Editor levels are not real runs
+    if *game_mode == GameMode::Playtest {
+        return;
+    }
+    run.ticks += 1;
+    run.distance += 1;
+    run.food += eat_reader.iter().count();
+    run.turns += turn_reader.iter().count();
+    run.length = run.length.max(amount_body_parts.0 + 1);
+    if let Ok(head_pos) = head_query.get_single() {
+        let right = (head_pos.x >= arena_size.width / 2) as usize;
+        let bottom = (head_pos.y >= arena_size.height / 2) as usize;
+        run.quadrants[bottom * 2 + right] += 1;
+    }
+    if let Some(GameOver(_, cause)) = game_over_reader.iter().next() {
+        run.death = Some(*cause);
+        end_run(&mut run, &mut lifetime, config.tick_step);
+    }
+}
+
+fn reset_run(mut run: ResMut<RunStats>) {
+    *run = RunStats::default();
+}
+
+// Whatever was played since the last death still counts
+fn finish_run(mut run: ResMut<RunStats>, mut lifetime: ResMut<LifetimeStats>, config: Res<Config>) {
+    if run.ticks != 0 {
+        end_run(&mut run, &mut lifetime, config.tick_step);
+    }
+}
+
+fn end_run(run: &mut RunStats, lifetime: &mut LifetimeStats, tick_step: f64) {
+    run.seconds = run.ticks as f64 * tick_step;
+    lifetime.add(run);
+    storage::save(STATS_FILE, lifetime);
+    *run = RunStats::default();
+}
@@ -0,0 +1,109 @@
+use super::{
+    arena::{ArenaSize, Position},
+    food::Food,
+    level::Level,
+    snake::{AmountBodyParts, BodyPart, DeathCause, GameOver, Head},
+    wall::spawn_wall,
+    AppState, GameMode,
+};
+use crate::theme::Theme;
+use bevy::prelude::*;
+
+const SURVIVAL_LEVEL: &str = include_str!("../../assets/levels/survival.ron");
+// Ticks between each ring of the arena closing
+const SHRINK_EVERY: usize = 80;
+// The arena stops closing once it is this small
+const MIN_PLAYABLE: i32 = 6;
+
+pub fn survival_level() -> Level {
+    Level::from_ron(SURVIVAL_LEVEL).expect("Bundled survival level is malformed")
+}
+
+#[derive(Debug, Default)]
+pub struct ShrinkState {
+    ticks: usize,
+    rings: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct ClosingWall;
+
+// Rings are counted from the outside, ring 0 is the arena border
+fn in_ring(pos: &Position, ring: i32, arena_size: &ArenaSize) -> bool {
+    let distance = pos
+        .x
+        .min(pos.y)
+        .min(arena_size.width - 1 - pos.x)
+        .min(arena_size.height - 1 - pos.y);
+    distance == ring
+}
+
+// The arena is closed by the snake fixed timestep set, see `SnakePlugin`
+pub struct SurvivalPlugin;
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShrinkState>()
+            .add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(reset_shrink));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn shrink_arena(
+    mut commands: Commands,
+    mut event_writer: EventWriter<GameOver>,
+    mut shrink_state: ResMut<ShrinkState>,
+    game_mode: Res<GameMode>,
+    theme: Res<Theme>,
+    arena_size: Res<ArenaSize>,
+    amount_body_parts: Res<AmountBodyParts>,
+    snake_query: Query<&Position, Or<(With<Head>, With<BodyPart>)>>,
+    food_query: Query<(Entity, &Position), With<Food>>,
+) {
+    if *game_mode != GameMode::Survival {
+        return;
+    }
+    shrink_state.ticks += 1;
+    if shrink_state.ticks % SHRINK_EVERY != 0 {
+        return;
+    }
+    let ring = shrink_state.rings;
+    let playable_width = arena_size.width - 2 * (ring + 1);
+    let playable_height = arena_size.height - 2 * (ring + 1);
+    if playable_width < MIN_PLAYABLE || playable_height < MIN_PLAYABLE {
+        return;
+    }
+    shrink_state.rings += 1;
+
+    for x in 0..arena_size.width {
+        for y in 0..arena_size.height {
+            let position = Position { x, y };
+            if in_ring(&position, ring, &arena_size) {
+                let ent = spawn_wall(&mut commands, &theme, position);
+                commands.entity(ent).insert(ClosingWall);
+            }
+        }
+    }
+    // Food that ends up inside the walls gets spawned again somewhere free
+    for (ent, pos) in food_query.iter() {
+        if in_ring(pos, ring, &arena_size) {
+            commands.entity(ent).despawn();
+        }
+    }
+    if snake_query
+        .iter()
+        .any(|pos| in_ring(pos, ring, &arena_size))
+    {
+        event_writer.send(GameOver(amount_body_parts.score(), DeathCause::ClosingWall));
+    }
+}
+
+pub fn reset_shrink(
+    mut commands: Commands,
+    mut shrink_state: ResMut<ShrinkState>,
+    wall_query: Query<Entity, With<ClosingWall>>,
+) {
+    for ent in wall_query.iter() {
+        commands.entity(ent).despawn();
+    }
+    *shrink_state = ShrinkState::default();
+}
@@ -0,0 +1,173 @@
+use super::{
+    replay::Recording,
+    snake::{EatEvent, GameOver},
+    AppState, GameMode,
+};
+use crate::config::Config;
+use crate::loading_state::GameAssets;
+use crate::storage;
+use crate::theme::Theme;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const TIME_BUDGET: f64 = 120.0;
+const FOOD_BONUS: f64 = 5.0;
+const HIGH_SCORES_FILE: &str = "time_attack.ron";
+const MAX_HIGH_SCORES: usize = 10;
+const COUNTDOWN_LOW: f64 = 10.0;
+
+// Best time attack scores, highest first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TimeAttackScores {
+    pub scores: Vec<usize>,
+}
+impl TimeAttackScores {
+    // Gives the rank of the new score if it made it into the table
+    fn insert(&mut self, score: usize) -> Option<usize> {
+        let rank = self
+            .scores
+            .iter()
+            .position(|s| score > *s)
+            .unwrap_or(self.scores.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.scores.insert(rank, score);
+        self.scores.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+// The last finished run, shown by the high score screen. The table opened
+// from the menu has none.
+#[derive(Debug, Default)]
+pub struct LastTimeAttack(pub Option<TimeAttackRun>);
+
+#[derive(Debug)]
+pub struct TimeAttackRun {
+    pub score: usize,
+    pub rank: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct TimeAttackClock {
+    remaining: f64,
+    eaten: usize,
+    finished: bool,
+}
+impl Default for TimeAttackClock {
+    fn default() -> Self {
+        TimeAttackClock {
+            remaining: TIME_BUDGET,
+            eaten: 0,
+            finished: false,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct CountdownText;
+
+// The clock is ticked by the snake fixed timestep set, see `SnakePlugin`
+pub struct TimeAttackPlugin;
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<TimeAttackScores>(HIGH_SCORES_FILE))
+            .init_resource::<TimeAttackClock>()
+            .init_resource::<LastTimeAttack>()
+            .add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(spawn_countdown))
+            .add_system_set(SystemSet::on_update(AppState::PlayState).with_system(update_countdown))
+            .add_system_set(SystemSet::on_exit(AppState::PlayState).with_system(destroy_countdown));
+    }
+}
+
+fn spawn_countdown(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    config: Res<Config>,
+    game_mode: Res<GameMode>,
+    theme: Res<Theme>,
+) {
+    commands.insert_resource(TimeAttackClock::default());
+    if *game_mode != GameMode::TimeAttack {
+        return;
+    }
+    let font = game_assets.font.clone();
+    let text_style = TextStyle {
+        font,
+        font_size: 40.0 * config.text_scale,
+        color: theme.countdown,
+    };
+    let text_alignment = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(format!("{:.0}", TIME_BUDGET), text_style, text_alignment),
+            transform: Transform::from_xyz(0.0, 110.0, 1.0),
+            ..Default::default()
+        })
+        .insert(CountdownText);
+}
+
+fn update_countdown(
+    clock: Res<TimeAttackClock>,
+    theme: Res<Theme>,
+    mut query: Query<&mut Text, With<CountdownText>>,
+) {
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!("{:.1}", clock.remaining.max(0.0));
+        text.sections[0].style.color = if clock.remaining <= COUNTDOWN_LOW {
+            theme.countdown_low
+        } else {
+            theme.countdown
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn tick_clock(
+    mut eat_reader: EventReader<EatEvent>,
+    mut game_over_reader: EventReader<GameOver>,
+    mut clock: ResMut<TimeAttackClock>,
+    mut scores: ResMut<TimeAttackScores>,
+    mut last_run: ResMut<LastTimeAttack>,
+    mut app_state: ResMut<State<AppState>>,
+    game_mode: Res<GameMode>,
+    config: Res<Config>,
+    recording: Option<Res<Recording>>,
+) {
+    if *game_mode != GameMode::TimeAttack || clock.finished {
+        return;
+    }
+    let eaten = eat_reader.iter().count();
+    clock.eaten += eaten;
+    clock.remaining += FOOD_BONUS * eaten as f64 - config.tick_step;
+    // Running out of time or dying both end the run
+    let died = game_over_reader.iter().next().is_some();
+    if clock.remaining > 0.0 && !died {
+        return;
+    }
+    clock.finished = true;
+    // A replay shows its score but doesn't add it again
+    let rank = match recording.as_deref() {
+        Some(recording) if recording.is_playing_back() => None,
+        _ => scores.insert(clock.eaten),
+    };
+    if rank.is_some() {
+        storage::save(HIGH_SCORES_FILE, &*scores);
+    }
+    last_run.0 = Some(TimeAttackRun {
+        score: clock.eaten,
+        rank,
+    });
+    // The score is shown even when Escape queued leaving on this frame
+    app_state.overwrite_set(AppState::HighScoresState).unwrap();
+}
+
+fn destroy_countdown(mut commands: Commands, query: Query<Entity, With<CountdownText>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+}
@@ -0,0 +1,336 @@
+use super::{
+    snake::{Direction, SteerEvent},
+    AppState,
+};
+use crate::theme::{Palette, Theme, Themed};
+use crate::{storage, GameSize};
+use bevy::{
+    input::touch::{TouchInput, TouchPhase},
+    prelude::*,
+    render::camera::Camera,
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+pub const TOUCH_FILE: &str = "touch.ron";
+// Clicks and drags with the mouse count as one more finger, on the web taps
+// come in as mouse clicks
+const MOUSE_ID: u64 = u64::MAX;
+// The D-pad sits at the bottom right of the game area
+const BUTTON_SIZE: f32 = 56.0;
+const BUTTON_GAP: f32 = 4.0;
+const DPAD_MARGIN: f32 = 20.0;
+// World depth of the D-pad, same as the mini-map, they never overlap
+const DPAD_Z: f32 = 60.0;
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Left,
+    Direction::Right,
+    Direction::Up,
+    Direction::Down,
+];
+
+// How touches steer, saved between sessions. Distances are in game units.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TouchSettings {
+    // How far a finger has to travel before it counts as a swipe
+    pub min_distance: f32,
+    // How many degrees a swipe may be off straight, more is ignored
+    pub angle_tolerance: f32,
+    pub dpad: bool,
+}
+impl Default for TouchSettings {
+    fn default() -> Self {
+        TouchSettings {
+            min_distance: 40.0,
+            angle_tolerance: 30.0,
+            dpad: false,
+        }
+    }
+}
+
+// Turns finger movement into turns, it knows nothing about bevy input so it
+// can be fed made up touches. A swipe steers as soon as it is long enough and
+// then starts over, so one long drag can turn more than once.
+#[derive(Debug, Default)]
+pub struct SwipeRecognizer {
+    // Where every finger down started its current swipe
+    starts: HashMap<u64, Vec2>,
+}
+impl SwipeRecognizer {
+    pub fn press(&mut self, id: u64, position: Vec2) {
+        self.starts.insert(id, position);
+    }
+
+    pub fn drag(&mut self, id: u64, position: Vec2, settings: &TouchSettings) -> Option<Direction> {
+        let start = self.starts.get_mut(&id)?;
+        let swipe = position - *start;
+        if swipe.length() < settings.min_distance {
+            return None;
+        }
+        // The next swipe starts here, also after one too far off straight to tell
+        *start = position;
+        let (along, across) = if swipe.x.abs() >= swipe.y.abs() {
+            (swipe.x.abs(), swipe.y.abs())
+        } else {
+            (swipe.y.abs(), swipe.x.abs())
+        };
+        if across.atan2(along).to_degrees() > settings.angle_tolerance {
+            return None;
+        }
+        // Up is positive y, the same as in the world
+        Some(if swipe.x.abs() >= swipe.y.abs() {
+            if swipe.x > 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if swipe.y > 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        })
+    }
+
+    pub fn release(
+        &mut self,
+        id: u64,
+        position: Vec2,
+        settings: &TouchSettings,
+    ) -> Option<Direction> {
+        let direction = self.drag(id, position, settings);
+        self.starts.remove(&id);
+        direction
+    }
+
+    pub fn cancel(&mut self, id: u64) {
+        self.starts.remove(&id);
+    }
+}
+
+#[derive(Component, Debug)]
+struct DpadButton(Direction);
+
+pub struct TouchPlugin;
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<TouchSettings>(TOUCH_FILE))
+            .init_resource::<SwipeRecognizer>()
+            .add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(spawn_dpad))
+            .add_system_set(
+                SystemSet::on_update(AppState::PlayState)
+                    .with_system(steer_by_touch)
+                    .with_system(show_dpad),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::PlayState)
+                    .with_system(destroy_dpad)
+                    .with_system(forget_touches),
+            );
+    }
+}
+
+// Where the middle of every button is, in game units from the middle of the view
+fn button_center(direction: Direction, game_size: &GameSize) -> Vec2 {
+    let step = BUTTON_SIZE + BUTTON_GAP;
+    let center = Vec2::new(game_size.width, -game_size.height) / 2.0
+        + Vec2::new(-1.0, 1.0) * (DPAD_MARGIN + BUTTON_SIZE / 2.0 + step);
+    center
+        + match direction {
+            Direction::Left => Vec2::new(-step, 0.0),
+            Direction::Right => Vec2::new(step, 0.0),
+            Direction::Up => Vec2::new(0.0, step),
+            Direction::Down => Vec2::new(0.0, -step),
+        }
+}
+
+// The buttons are children of the camera so they stay put while it follows
+fn spawn_dpad(
+    mut commands: Commands,
+    game_size: Res<GameSize>,
+    theme: Res<Theme>,
+    camera_query: Query<(Entity, &Transform), With<Camera>>,
+) {
+    let (camera, camera_transform) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => panic!("HOW DID WE EVEN GET HERE!?!? No camera to put the D-pad on"),
+    };
+    let mut color = theme.controls;
+    color.set_a(0.4);
+    for direction in DIRECTIONS {
+        let center = button_center(direction, &game_size);
+        let button = commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(BUTTON_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(
+                    center.x,
+                    center.y,
+                    DPAD_Z - camera_transform.translation.z,
+                ),
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(DpadButton(direction))
+            .insert(Themed(Palette::Controls))
+            .id();
+        commands.entity(camera).add_child(button);
+    }
+}
+
+fn show_dpad(settings: Res<TouchSettings>, mut query: Query<&mut Visibility, With<DpadButton>>) {
+    for mut visibility in query.iter_mut() {
+        visibility.is_visible = settings.dpad;
+    }
+}
+
+fn dpad_at(point: Vec2, game_size: &GameSize) -> Option<Direction> {
+    DIRECTIONS.into_iter().find(|direction| {
+        let offset = point - button_center(*direction, game_size);
+        offset.x.abs() <= BUTTON_SIZE / 2.0 && offset.y.abs() <= BUTTON_SIZE / 2.0
+    })
+}
+
+// Both fingers and the mouse go through the same recogniser. A finger that
+// comes down on the D-pad presses that button and is not a swipe.
+#[allow(clippy::too_many_arguments)]
+fn steer_by_touch(
+    windows: Res<Windows>,
+    game_size: Res<GameSize>,
+    settings: Res<TouchSettings>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut recognizer: ResMut<SwipeRecognizer>,
+    mut touch_reader: EventReader<TouchInput>,
+    mut cursor_reader: EventReader<CursorMoved>,
+    mut steer_writer: EventWriter<SteerEvent>,
+) {
+    let win = match windows.get_primary() {
+        Some(win) => win,
+        None => return,
+    };
+    let mut pointers: Vec<(u64, TouchPhase, Vec2)> = touch_reader
+        .iter()
+        .map(|touch| (touch.id, touch.phase, touch.position))
+        .collect();
+    if mouse_input.just_pressed(MouseButton::Left) {
+        if let Some(position) = win.cursor_position() {
+            pointers.push((MOUSE_ID, TouchPhase::Started, position));
+        }
+    }
+    if mouse_input.pressed(MouseButton::Left) {
+        for moved in cursor_reader.iter() {
+            pointers.push((MOUSE_ID, TouchPhase::Moved, moved.position));
+        }
+    }
+    if mouse_input.just_released(MouseButton::Left) {
+        match win.cursor_position() {
+            Some(position) => pointers.push((MOUSE_ID, TouchPhase::Ended, position)),
+            None => pointers.push((MOUSE_ID, TouchPhase::Cancelled, Vec2::ZERO)),
+        }
+    }
+
+    for (id, phase, position) in pointers {
+        let point = game_size.window_to_world(win, position);
+        let direction = match phase {
+            TouchPhase::Started => match dpad_at(point, &game_size) {
+                Some(direction) if settings.dpad => Some(direction),
+                _ => {
+                    recognizer.press(id, point);
+                    None
+                }
+            },
+            TouchPhase::Moved => recognizer.drag(id, point, &settings),
+            TouchPhase::Ended => recognizer.release(id, point, &settings),
+            TouchPhase::Cancelled => {
+                recognizer.cancel(id);
+                None
+            }
+        };
+        if let Some(direction) = direction {
+            steer_writer.send(SteerEvent(direction));
+        }
+    }
+}
+
+fn forget_touches(mut recognizer: ResMut<SwipeRecognizer>) {
+    *recognizer = SwipeRecognizer::default();
+}
+
+fn destroy_dpad(mut commands: Commands, query: Query<Entity, With<DpadButton>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINGER: u64 = 1;
+
+    fn at(x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y)
+    }
+
+    #[test]
+    fn short_drags_do_nothing() {
+        let settings = TouchSettings::default();
+        let mut swipes = SwipeRecognizer::default();
+        swipes.press(FINGER, at(0.0, 0.0));
+        assert_eq!(swipes.drag(FINGER, at(20.0, 0.0), &settings), None);
+        assert_eq!(swipes.drag(FINGER, at(39.0, 0.0), &settings), None);
+        // Still measured from where the finger came down
+        assert_eq!(
+            swipes.drag(FINGER, at(41.0, 0.0), &settings),
+            Some(Direction::Right)
+        );
+    }
+
+    #[test]
+    fn crooked_swipes_do_nothing_and_start_over() {
+        let settings = TouchSettings::default();
+        let mut swipes = SwipeRecognizer::default();
+        swipes.press(FINGER, at(0.0, 0.0));
+        assert_eq!(swipes.drag(FINGER, at(40.0, 40.0), &settings), None);
+        // From where it came down this would be right, from the crooked one it is down
+        assert_eq!(
+            swipes.drag(FINGER, at(40.0, 0.0), &settings),
+            Some(Direction::Down)
+        );
+    }
+
+    #[test]
+    fn one_long_drag_turns_twice() {
+        let settings = TouchSettings::default();
+        let mut swipes = SwipeRecognizer::default();
+        swipes.press(FINGER, at(0.0, 0.0));
+        assert_eq!(
+            swipes.drag(FINGER, at(45.0, 0.0), &settings),
+            Some(Direction::Right)
+        );
+        assert_eq!(swipes.drag(FINGER, at(45.0, 30.0), &settings), None);
+        assert_eq!(
+            swipes.release(FINGER, at(45.0, 45.0), &settings),
+            Some(Direction::Up)
+        );
+        assert_eq!(swipes.drag(FINGER, at(0.0, 0.0), &settings), None);
+    }
+
+    #[test]
+    fn cancelled_fingers_are_forgotten() {
+        let settings = TouchSettings::default();
+        let mut swipes = SwipeRecognizer::default();
+        swipes.press(FINGER, at(0.0, 0.0));
+        swipes.press(FINGER + 1, at(0.0, 0.0));
+        swipes.cancel(FINGER);
+        assert_eq!(swipes.drag(FINGER, at(100.0, 0.0), &settings), None);
+        assert_eq!(swipes.release(FINGER, at(100.0, 0.0), &settings), None);
+        // Other fingers carry on
+        assert_eq!(
+            swipes.drag(FINGER + 1, at(0.0, -100.0), &settings),
+            Some(Direction::Down)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use super::{arena::Position, level::CurrentLevel, AppState};
+use crate::theme::{Palette, Theme, Themed};
+use bevy::prelude::*;
+
+#[derive(Component, Debug)]
+pub struct Wall;
+
+#[derive(Bundle)]
+struct WallBundle {
+    wall: Wall,
+    position: Position,
+    themed: Themed,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+pub struct WallPlugin;
+impl Plugin for WallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::PlayState).with_system(spawn_walls));
+    }
+}
+
+fn spawn_walls(mut commands: Commands, level: Res<CurrentLevel>, theme: Res<Theme>) {
+    for position in level.0.walls.iter() {
+        spawn_wall(&mut commands, &theme, *position);
+    }
+}
+
+pub fn spawn_wall(commands: &mut Commands, theme: &Theme, position: Position) -> Entity {
+    commands
+        .spawn_bundle(WallBundle {
+            wall: Wall,
+            position,
+            themed: Themed(Palette::Wall),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: theme.wall,
+                    custom_size: Some(Vec2::ONE),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 5.0),
+                ..Default::default()
+            },
+        })
+        .id()
+}
@@ -0,0 +1,162 @@
+use crate::play_state::{
+    snake::{AmountBodyParts, EatEvent, GameOver, TurnEvent},
+    Paused,
+};
+use crate::{storage, synth, AppState};
+use bevy::prelude::*;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+pub const SOUND_FILE: &str = "sound.ron";
+
+// Sound cues, anything can ask for one by sending a `PlaySound`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    MenuMove,
+    MenuSelect,
+    Eat,
+    Turn,
+    Pause,
+    GameOver,
+}
+impl Sound {
+    // Sounds are synthesised when played, eating sounds higher the longer the snake is
+    fn render(&self, length: usize) -> Vec<u8> {
+        let samples = match self {
+            Sound::MenuMove => synth::menu_move(),
+            Sound::MenuSelect => synth::menu_select(),
+            Sound::Eat => synth::eat(length),
+            Sound::Turn => synth::turn(),
+            Sound::Pause => synth::pause(),
+            Sound::GameOver => synth::game_over(),
+        };
+        synth::to_wav(&samples)
+    }
+}
+
+pub struct PlaySound(pub Sound);
+
+// Saved between sessions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SoundSettings {
+    pub volume: f32,
+    pub muted: bool,
+}
+impl Default for SoundSettings {
+    fn default() -> Self {
+        SoundSettings {
+            volume: 0.8,
+            muted: false,
+        }
+    }
+}
+
+// The stream has to be kept alive for the handle to play anything. Both are
+// missing when there is no output device, then the game just stays silent.
+struct AudioDevice {
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    // Dropping the sink stops the music
+    music: Option<Sink>,
+}
+impl AudioDevice {
+    fn open() -> AudioDevice {
+        let output = match OutputStream::try_default() {
+            Ok(output) => Some(output),
+            Err(e) => {
+                warn!("No audio device, playing without sound: {}", e);
+                None
+            }
+        };
+        AudioDevice {
+            output,
+            music: None,
+        }
+    }
+}
+
+pub struct SoundPlugin;
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySound>()
+            .insert_resource(storage::load::<SoundSettings>(SOUND_FILE))
+            .insert_non_send_resource(AudioDevice::open())
+            .add_system(play_sounds)
+            .add_system(update_music);
+    }
+}
+
+fn play_sounds(
+    device: NonSend<AudioDevice>,
+    settings: Res<SoundSettings>,
+    amount_body_parts: Option<Res<AmountBodyParts>>,
+    mut sound_reader: EventReader<PlaySound>,
+    mut eat_reader: EventReader<EatEvent>,
+    mut turn_reader: EventReader<TurnEvent>,
+    mut game_over_reader: EventReader<GameOver>,
+) {
+    // Game events are turned into cues here so the game code knows nothing of sound
+    let mut cues: Vec<Sound> = sound_reader.iter().map(|sound| sound.0).collect();
+    cues.extend(eat_reader.iter().map(|_| Sound::Eat));
+    cues.extend(turn_reader.iter().map(|_| Sound::Turn));
+    cues.extend(game_over_reader.iter().map(|_| Sound::GameOver));
+
+    let handle = match &device.output {
+        Some((_, handle)) if !settings.muted => handle,
+        _ => return,
+    };
+    let length = amount_body_parts.map_or(0, |amount| amount.0 + 1);
+    for cue in cues {
+        let source = match Decoder::new(Cursor::new(cue.render(length))) {
+            Ok(source) => source,
+            Err(e) => {
+                warn!("Can't decode sound {:?}: {}", cue, e);
+                continue;
+            }
+        };
+        if let Err(e) = handle.play_raw(source.amplify(settings.volume).convert_samples()) {
+            warn!("Can't play sound {:?}: {}", cue, e);
+        }
+    }
+}
+
+// The background track loops while playing and holds while paused
+fn update_music(
+    mut device: NonSendMut<AudioDevice>,
+    settings: Res<SoundSettings>,
+    app_state: Res<State<AppState>>,
+    paused: Res<Paused>,
+) {
+    let device = &mut *device;
+    let handle = match &device.output {
+        Some((_, handle)) if !settings.muted && app_state.current() == &AppState::PlayState => {
+            handle
+        }
+        _ => {
+            device.music = None;
+            return;
+        }
+    };
+    if device.music.is_none() {
+        let sink = match Sink::try_new(handle) {
+            Ok(sink) => sink,
+            Err(e) => {
+                warn!("Can't play music: {}", e);
+                return;
+            }
+        };
+        match Decoder::new(Cursor::new(synth::to_wav(&synth::music()))) {
+            Ok(source) => sink.append(source.repeat_infinite()),
+            Err(e) => warn!("Can't decode music: {}", e),
+        }
+        device.music = Some(sink);
+    }
+    if let Some(music) = &device.music {
+        music.set_volume(settings.volume);
+        if paused.0 {
+            music.pause();
+        } else {
+            music.play();
+        }
+    }
+}
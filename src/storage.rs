@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+// Saved files live in the user's data dir as RON. There is no file system
+// in wasm, so there loading gives the defaults and saving does nothing.
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path(file: &str) -> Option<std::path::PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("snaky").join(file))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load<T: DeserializeOwned + Default>(file: &str) -> T {
+    let path = match save_path(file) {
+        Some(path) => path,
+        None => return T::default(),
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(source) => match ron::from_str(&source) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Ignoring malformed save file {}: {}", path.display(), e);
+                T::default()
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+        Err(e) => {
+            warn!("Can't read save file {}: {}", path.display(), e);
+            T::default()
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save<T: Serialize>(file: &str, value: &T) {
+    let path = match save_path(file) {
+        Some(path) => path,
+        None => return,
+    };
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::new())
+        .map_err(|e| e.to_string())
+        .and_then(|source| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&path, source).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        warn!("Can't write save file {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load<T: DeserializeOwned + Default>(_file: &str) -> T {
+    T::default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save<T: Serialize>(_file: &str, _value: &T) {}
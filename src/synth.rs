@@ -0,0 +1,235 @@
+// Tiny chiptune synthesiser. Every sound of the game is rendered here to mono
+// PCM and wrapped as a WAV file, so nothing has to be shipped as an asset.
+// Only std is used, the render_synth example includes this file on its own.
+
+pub const SAMPLE_RATE: u32 = 22050;
+// Fade in and out of every note, stops clicks between notes
+const EDGE_SECS: f32 = 0.004;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wave {
+    Square,
+    Triangle,
+}
+
+// A `freq` of 0 is a rest
+#[derive(Debug, Clone, Copy)]
+pub struct Note {
+    pub wave: Wave,
+    pub freq: f32,
+    pub secs: f32,
+    pub volume: f32,
+}
+
+fn note(wave: Wave, freq: f32, secs: f32, volume: f32) -> Note {
+    Note {
+        wave,
+        freq,
+        secs,
+        volume,
+    }
+}
+
+// Frequency of the note `semitones` away from A4
+pub fn pitch(semitones: i32) -> f32 {
+    440.0 * 2f32.powf(semitones as f32 / 12.0)
+}
+
+pub fn render(notes: &[Note]) -> Vec<f32> {
+    let mut samples = Vec::new();
+    for note in notes {
+        let length = (note.secs * SAMPLE_RATE as f32) as usize;
+        let edge = ((EDGE_SECS * SAMPLE_RATE as f32) as usize).max(1);
+        for i in 0..length {
+            let phase = (i as f32 * note.freq / SAMPLE_RATE as f32).fract();
+            let value = match note.wave {
+                _ if note.freq <= 0.0 => 0.0,
+                Wave::Square if phase < 0.5 => 1.0,
+                Wave::Square => -1.0,
+                Wave::Triangle => 4.0 * (phase - 0.5).abs() - 1.0,
+            };
+            let envelope = (i.min(length - 1 - i) as f32 / edge as f32).min(1.0);
+            samples.push(value * envelope * note.volume);
+        }
+    }
+    samples
+}
+
+// Plays voices at the same time, the result is as long as the longest one
+pub fn mix(voices: &[Vec<f32>]) -> Vec<f32> {
+    let length = voices.iter().map(|voice| voice.len()).max().unwrap_or(0);
+    let mut samples = vec![0.0; length];
+    for voice in voices {
+        for (sample, value) in samples.iter_mut().zip(voice.iter()) {
+            *sample += value;
+        }
+    }
+    samples
+}
+
+// 16 bit mono PCM WAV, what rodio's decoder and any audio player understand
+pub fn to_wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+pub fn menu_move() -> Vec<f32> {
+    render(&[note(Wave::Square, pitch(7), 0.04, 0.2)])
+}
+
+pub fn menu_select() -> Vec<f32> {
+    render(&[
+        note(Wave::Square, pitch(7), 0.05, 0.25),
+        note(Wave::Square, pitch(14), 0.08, 0.25),
+    ])
+}
+
+// Goes up a semitone every two body parts, for two octaves at most
+pub fn eat(length: usize) -> Vec<f32> {
+    let rise = (length / 2).min(24) as i32;
+    render(&[
+        note(Wave::Square, pitch(3 + rise), 0.04, 0.3),
+        note(Wave::Square, pitch(10 + rise), 0.06, 0.3),
+    ])
+}
+
+pub fn turn() -> Vec<f32> {
+    render(&[note(Wave::Triangle, pitch(-12), 0.025, 0.12)])
+}
+
+pub fn pause() -> Vec<f32> {
+    render(&[
+        note(Wave::Triangle, pitch(0), 0.06, 0.25),
+        note(Wave::Triangle, 0.0, 0.03, 0.0),
+        note(Wave::Triangle, pitch(0), 0.06, 0.25),
+    ])
+}
+
+pub fn game_over() -> Vec<f32> {
+    render(&[
+        note(Wave::Square, pitch(-2), 0.12, 0.3),
+        note(Wave::Square, pitch(-5), 0.12, 0.3),
+        note(Wave::Square, pitch(-9), 0.25, 0.3),
+    ])
+}
+
+// Four bars of eighth notes at 120 bpm, made to be looped
+pub fn music() -> Vec<f32> {
+    const EIGHTH: f32 = 0.25;
+    const MELODY: [i32; 32] = [
+        3, 7, 10, 7, 3, 7, 10, 15, 1, 5, 8, 5, 1, 5, 8, 13, //
+        -2, 3, 7, 3, -2, 3, 7, 10, 0, 3, 7, 12, 10, 7, 3, 0,
+    ];
+    const BASS: [i32; 4] = [-21, -23, -26, -24];
+    let melody: Vec<Note> = MELODY
+        .iter()
+        .map(|semitones| note(Wave::Square, pitch(*semitones), EIGHTH, 0.08))
+        .collect();
+    // Every bass note is a bar long, pulsing on the quarters
+    let bass: Vec<Note> = BASS
+        .iter()
+        .flat_map(|semitones| {
+            (0..4).map(move |_| note(Wave::Triangle, pitch(*semitones), EIGHTH * 2.0, 0.2))
+        })
+        .collect();
+    mix(&[render(&melody), render(&bass)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(wav: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([wav[at], wav[at + 1], wav[at + 2], wav[at + 3]])
+    }
+
+    // The samples back out of a WAV file
+    fn samples(wav: &[u8]) -> Vec<i16> {
+        wav[44..]
+            .chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect()
+    }
+
+    // Twice for every period of the wave, so it goes up with the pitch
+    fn zero_crossings(wav: &[u8]) -> usize {
+        samples(wav)
+            .windows(2)
+            .filter(|pair| (pair[0] < 0) != (pair[1] < 0))
+            .count()
+    }
+
+    #[test]
+    fn wav_header() {
+        let sound = eat(2);
+        let wav = to_wav(&sound);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32_at(&wav, 4) as usize, wav.len() - 8);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&wav, 24), SAMPLE_RATE);
+        // Bytes a second, two for every sample
+        assert_eq!(u32_at(&wav, 28), SAMPLE_RATE * 2);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32_at(&wav, 40) as usize, sound.len() * 2);
+        assert_eq!(wav.len(), 44 + sound.len() * 2);
+    }
+
+    #[test]
+    fn wav_samples_are_clamped() {
+        let wav = to_wav(&[0.0, 0.5, 1.0, 2.0, -2.0]);
+        assert_eq!(
+            samples(&wav),
+            [0, i16::MAX / 2, i16::MAX, i16::MAX, -i16::MAX]
+        );
+    }
+
+    #[test]
+    fn notes_last_as_long_as_asked() {
+        let notes = [
+            note(Wave::Square, pitch(0), 0.1, 0.5),
+            note(Wave::Triangle, 0.0, 0.05, 0.5),
+        ];
+        let expected = (0.1 * SAMPLE_RATE as f32) as usize + (0.05 * SAMPLE_RATE as f32) as usize;
+        assert_eq!(render(&notes).len(), expected);
+        // A rest is silent
+        assert!(render(&notes[1..]).iter().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn a_note_plays_its_pitch() {
+        let wav = to_wav(&render(&[note(Wave::Square, 440.0, 1.0, 0.5)]));
+        assert!((zero_crossings(&wav) as i32 - 880).abs() <= 2);
+    }
+
+    #[test]
+    fn eating_rises_with_length() {
+        let crossings: Vec<usize> = [2, 10, 20, 40]
+            .iter()
+            .map(|length| zero_crossings(&to_wav(&eat(*length))))
+            .collect();
+        assert!(crossings.windows(2).all(|pair| pair[0] < pair[1]));
+        // Two octaves up at most
+        assert_eq!(
+            zero_crossings(&to_wav(&eat(48))),
+            zero_crossings(&to_wav(&eat(500)))
+        );
+    }
+}
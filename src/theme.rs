@@ -0,0 +1,214 @@
+use crate::loading_state::GameAssets;
+use crate::storage;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+// Bundled presets, loaded through the asset server so edits show up while playing,
+// see `GameAssets`
+pub const THEMES: [&str; 3] = ["classic", "high_contrast", "colour_blind"];
+const DEFAULT_THEME: &str = include_str!("../assets/themes/classic.theme.ron");
+pub const THEME_FILE: &str = "theme.ron";
+
+// Every colour of the game. The active one is a resource, changing it recolours
+// everything marked `Themed` on the next frame.
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "5b0f1c7e-3f5a-4d8e-9a51-2c6d7e8f9a10"]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    pub letterbox: Color,
+    pub head: Color,
+    pub body: Color,
+    pub food: Color,
+    pub wall: Color,
+    pub portal: Color,
+    pub hazard: Color,
+    pub key: Color,
+    pub score: Color,
+    pub countdown: Color,
+    pub countdown_low: Color,
+    pub title: Color,
+    pub header: Color,
+    pub selected: Color,
+    pub item: Color,
+    pub locked: Color,
+    pub controls: Color,
+    pub highlight: Color,
+    pub editor_background: Color,
+    pub editor_cursor: Color,
+    pub pending_portal: Color,
+    pub hud: Color,
+}
+impl Default for Theme {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_THEME).expect("Bundled classic theme is malformed")
+    }
+}
+impl Theme {
+    pub fn color(&self, palette: Palette) -> Color {
+        match palette {
+            Palette::Letterbox => self.letterbox,
+            Palette::Head => self.head,
+            Palette::Body => self.body,
+            Palette::Food => self.food,
+            Palette::Wall => self.wall,
+            Palette::Portal => self.portal,
+            Palette::Hazard => self.hazard,
+            Palette::Key => self.key,
+            Palette::Score => self.score,
+            Palette::Title => self.title,
+            Palette::Header => self.header,
+            Palette::Selected => self.selected,
+            Palette::Item => self.item,
+            Palette::Locked => self.locked,
+            Palette::Controls => self.controls,
+            Palette::Highlight => self.highlight,
+            Palette::EditorBackground => self.editor_background,
+            Palette::EditorCursor => self.editor_cursor,
+            Palette::PendingPortal => self.pending_portal,
+            Palette::Hud => self.hud,
+        }
+    }
+}
+
+// The theme colours an entity can be drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    Letterbox,
+    Head,
+    Body,
+    Food,
+    Wall,
+    Portal,
+    Hazard,
+    Key,
+    Score,
+    Title,
+    Header,
+    Selected,
+    Item,
+    Locked,
+    Controls,
+    Highlight,
+    EditorBackground,
+    EditorCursor,
+    PendingPortal,
+    Hud,
+}
+
+// Sprites and texts with this get recoloured when the theme changes
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Themed(pub Palette);
+
+// Which preset is picked, saved between sessions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThemeChoice {
+    pub name: String,
+}
+impl Default for ThemeChoice {
+    fn default() -> Self {
+        ThemeChoice {
+            name: THEMES[0].to_string(),
+        }
+    }
+}
+impl ThemeChoice {
+    fn index(&self) -> usize {
+        THEMES
+            .iter()
+            .position(|name| *name == self.name)
+            .unwrap_or(0)
+    }
+
+    pub fn next(&mut self) {
+        self.name = THEMES[(self.index() + 1) % THEMES.len()].to_string();
+    }
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let theme: Theme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+pub struct ThemePlugin;
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        let theme = Theme::default();
+        app.add_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .insert_resource(ClearColor(theme.background))
+            .insert_resource(theme)
+            .insert_resource(storage::load::<ThemeChoice>(THEME_FILE))
+            .add_system(apply_theme)
+            .add_system(recolor);
+    }
+}
+
+// Copies the picked theme into the resource when it is picked, loaded or edited
+fn apply_theme(
+    mut events: EventReader<AssetEvent<Theme>>,
+    choice: Res<ThemeChoice>,
+    game_assets: Res<GameAssets>,
+    themes: Res<Assets<Theme>>,
+    mut theme: ResMut<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    let handle = &game_assets.themes[choice.index()];
+    let reloaded = events.iter().any(|event| match event {
+        AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h } => h == handle,
+        AssetEvent::Removed { .. } => false,
+    });
+    if !reloaded && !choice.is_changed() {
+        return;
+    }
+    if let Some(picked) = themes.get(handle) {
+        *theme = picked.clone();
+        clear_color.0 = theme.background;
+    }
+}
+
+// Faded entities stay as faded as they were
+fn recolor(
+    theme: Res<Theme>,
+    mut sprite_query: Query<(&Themed, &mut Sprite)>,
+    mut sheet_query: Query<(&Themed, &mut TextureAtlasSprite)>,
+    mut text_query: Query<(&Themed, &mut Text)>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+    for (themed, mut sprite) in sprite_query.iter_mut() {
+        let alpha = sprite.color.a();
+        sprite.color = *theme.color(themed.0).set_a(alpha);
+    }
+    for (themed, mut sprite) in sheet_query.iter_mut() {
+        let alpha = sprite.color.a();
+        sprite.color = *theme.color(themed.0).set_a(alpha);
+    }
+    for (themed, mut text) in text_query.iter_mut() {
+        for section in text.sections.iter_mut() {
+            let alpha = section.style.color.a();
+            section.style.color = *theme.color(themed.0).set_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,562 @@
+use crate::cli::{parse_arena, read_level};
+use crate::config::Config;
+use crate::play_state::level::Level;
+use bots::Bot;
+use game::{is_plain, Game};
+use std::collections::{HashMap, HashSet};
+
+mod bots;
+mod game;
+
+const USAGE: &str = "\
+Usage: snaky tournament --bot <BOT> --bot <BOT> [OPTIONS]
+
+Plays every game headless and prints the standings and the head to head
+results, ratings are Elo starting at 1500. Games follow the classic rules,
+snakes start as long as in the game and biting a body is always deadly.
+
+Bots:
+  random, greedy, cautious   Built in
+  exec:<PROGRAM> [ARGS]      Any program, see src/tournament/bots.rs for what
+                             it is told and has to answer
+
+Options:
+  --bot <BOT>         Takes part, give two or more
+  --swiss             Pair by standings each round instead of everyone
+                      against everyone
+  --rounds <N>        Rounds of a swiss tournament, enough to tell a winner
+                      by default
+  --seeds <N>         Seeds every pairing plays, from both sides [default: 10]
+  --seed <N>          First seed [default: 0]
+  --level <FILE>      Play on a level saved by the editor, walls only as
+                      portals, hazards, keys and fixed food aren't played
+  --arena <W>x<H>     Size of the arena, like 60x40 [default: 40x30]
+  --json              Print JSON instead of CSV
+  -h, --help          Show this and quit";
+
+const START_RATING: f64 = 1500.0;
+// How far one game moves a rating
+const RATING_K: f64 = 16.0;
+
+#[derive(Debug)]
+struct Options {
+    bots: Vec<(String, Bot)>,
+    swiss: bool,
+    rounds: Option<usize>,
+    seeds: u64,
+    first_seed: u64,
+    level: Level,
+    // Cells a snake covers when it spawns, like in the game
+    starting_length: usize,
+    json: bool,
+}
+
+// Runs `snaky tournament ...` and gives the exit code
+pub fn run(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return 0;
+    }
+    let options = match parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("snaky tournament: {}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    let mut tournament = Tournament::new(options.bots.len());
+    let played = if options.swiss {
+        play_swiss(&options, &mut tournament)
+    } else {
+        play_round_robin(&options, &mut tournament)
+    };
+    if let Err(e) = played {
+        eprintln!("snaky tournament: {}", e);
+        return 1;
+    }
+    let names: Vec<&str> = options.bots.iter().map(|(name, _)| name.as_str()).collect();
+    if options.json {
+        println!("{}", tournament.to_json(&names));
+    } else {
+        print!("{}", tournament.to_csv(&names));
+    }
+    0
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        bots: Vec::new(),
+        swiss: false,
+        rounds: None,
+        seeds: 10,
+        first_seed: 0,
+        level: Level::classic(&Config::default()),
+        starting_length: Config::default().starting_length,
+        json: false,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        // Values come after a space or an equals sign, like the launch options
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        let mut value = || {
+            inline
+                .or_else(|| args.next().map(|arg| arg.as_str()))
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        let number = |value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("{} must be a whole number, got {}", flag, value))
+        };
+        match flag {
+            "--bot" => {
+                let spec = value()?;
+                let bot = Bot::parse(spec)?;
+                // The same bot twice is told apart by its place
+                let mut name = spec.to_string();
+                let mut count = 1;
+                while options.bots.iter().any(|(taken, _)| *taken == name) {
+                    count += 1;
+                    name = format!("{}#{}", spec, count);
+                }
+                options.bots.push((name, bot));
+            }
+            "--swiss" if inline.is_none() => options.swiss = true,
+            "--rounds" => match number(value()?)? {
+                0 => return Err(format!("{} must be 1 or more", flag)),
+                rounds => options.rounds = Some(rounds as usize),
+            },
+            "--seeds" => match number(value()?)? {
+                0 => return Err(format!("{} must be 1 or more", flag)),
+                seeds => options.seeds = seeds,
+            },
+            "--seed" => options.first_seed = number(value()?)?,
+            "--level" => options.level = read_level(value()?)?,
+            "--arena" => {
+                let (width, height) = parse_arena(flag, value()?)?;
+                options.level = options.level.resized(width, height);
+            }
+            "--json" if inline.is_none() => options.json = true,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    if options.bots.len() < 2 {
+        return Err("give two or more --bot".to_string());
+    }
+    if !is_plain(&options.level) {
+        return Err(
+            "--level: only walls are played, take out the portals, hazards, keys and fixed food"
+                .to_string(),
+        );
+    }
+    Ok(options)
+}
+
+// How a bot did against one other bot
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Record {
+    wins: usize,
+    losses: usize,
+    draws: usize,
+}
+impl Record {
+    fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+}
+
+#[derive(Debug)]
+struct Tournament {
+    ratings: Vec<f64>,
+    // Bye points of a swiss round without an opponent
+    byes: Vec<f64>,
+    // Every pair that played, by the bot listed first
+    records: HashMap<(usize, usize), Record>,
+}
+impl Tournament {
+    fn new(bots: usize) -> Tournament {
+        Tournament {
+            ratings: vec![START_RATING; bots],
+            byes: vec![0.0; bots],
+            records: HashMap::new(),
+        }
+    }
+
+    // `winner` is `a` or `b`, none for a draw. Ratings move after every game,
+    // in the order they are played.
+    fn record(&mut self, a: usize, b: usize, winner: Option<usize>) {
+        let score = match winner {
+            Some(winner) if winner == a => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+        let expected = 1.0 / (1.0 + 10f64.powf((self.ratings[b] - self.ratings[a]) / 400.0));
+        let change = RATING_K * (score - expected);
+        self.ratings[a] += change;
+        self.ratings[b] -= change;
+        for (me, them, score) in [(a, b, score), (b, a, 1.0 - score)] {
+            let record = self.records.entry((me, them)).or_default();
+            if score > 0.5 {
+                record.wins += 1;
+            } else if score < 0.5 {
+                record.losses += 1;
+            } else {
+                record.draws += 1;
+            }
+        }
+    }
+
+    fn total(&self, bot: usize) -> Record {
+        let mut total = Record::default();
+        for ((me, _), record) in self.records.iter() {
+            if *me == bot {
+                total.wins += record.wins;
+                total.losses += record.losses;
+                total.draws += record.draws;
+            }
+        }
+        total
+    }
+
+    fn points(&self, bot: usize) -> f64 {
+        self.total(bot).points() + self.byes[bot]
+    }
+
+    // Most points first, the rating breaks ties
+    fn standings(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.ratings.len()).collect();
+        order.sort_by(|a, b| {
+            let key = |bot: usize| (self.points(bot), self.ratings[bot]);
+            key(*b)
+                .partial_cmp(&key(*a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        order
+    }
+
+    // Pairs in the order the bots were given
+    fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = self
+            .records
+            .keys()
+            .copied()
+            .filter(|(me, them)| me != them)
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    fn to_csv(&self, names: &[&str]) -> String {
+        let mut csv = String::from("bot,games,wins,losses,draws,points,rating\n");
+        for bot in self.standings() {
+            let total = self.total(bot);
+            csv += &format!(
+                "{},{},{},{},{},{},{:.1}\n",
+                csv_field(names[bot]),
+                total.games(),
+                total.wins,
+                total.losses,
+                total.draws,
+                self.points(bot),
+                self.ratings[bot]
+            );
+        }
+        csv += "\nbot,opponent,wins,losses,draws\n";
+        for (me, them) in self.pairs() {
+            let record = self.records[&(me, them)];
+            csv += &format!(
+                "{},{},{},{},{}\n",
+                csv_field(names[me]),
+                csv_field(names[them]),
+                record.wins,
+                record.losses,
+                record.draws
+            );
+        }
+        csv
+    }
+
+    fn to_json(&self, names: &[&str]) -> String {
+        let standings: Vec<String> = self
+            .standings()
+            .into_iter()
+            .map(|bot| {
+                let total = self.total(bot);
+                format!(
+                    "{{\"bot\":{},\"games\":{},\"wins\":{},\"losses\":{},\"draws\":{},\"points\":{},\"rating\":{:.1}}}",
+                    json_string(names[bot]),
+                    total.games(),
+                    total.wins,
+                    total.losses,
+                    total.draws,
+                    self.points(bot),
+                    self.ratings[bot]
+                )
+            })
+            .collect();
+        let pairs: Vec<String> = self
+            .pairs()
+            .into_iter()
+            .map(|(me, them)| {
+                let record = self.records[&(me, them)];
+                format!(
+                    "{{\"bot\":{},\"opponent\":{},\"wins\":{},\"losses\":{},\"draws\":{}}}",
+                    json_string(names[me]),
+                    json_string(names[them]),
+                    record.wins,
+                    record.losses,
+                    record.draws
+                )
+            })
+            .collect();
+        format!(
+            "{{\"standings\":[{}],\"head_to_head\":[{}]}}",
+            standings.join(","),
+            pairs.join(",")
+        )
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            c if (c as u32) < 0x20 => json += &format!("\\u{:04x}", c as u32),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// One game, `a` starts on the left. Gives the winner, none for a draw.
+fn play_game(options: &Options, a: usize, b: usize, seed: u64) -> Result<Option<usize>, String> {
+    let mut game = Game::new(&options.level, 2, options.starting_length, seed);
+    let players = [a, b];
+    let mut brains = Vec::new();
+    for (me, bot) in players.iter().enumerate() {
+        brains.push(options.bots[*bot].1.brain(&game, me, seed)?);
+    }
+    while !game.is_over() {
+        let turns: Vec<_> = brains
+            .iter_mut()
+            .enumerate()
+            .map(|(me, brain)| brain.turn(&game, me))
+            .collect();
+        game.step(&turns);
+    }
+    let winner = game.winner();
+    for (me, brain) in brains.iter_mut().enumerate() {
+        brain.end(winner.map(|winner| winner == me));
+    }
+    Ok(winner.map(|winner| players[winner]))
+}
+
+// Every seed from both sides, so neither bot is favoured by where it starts
+fn play_pairing(
+    options: &Options,
+    tournament: &mut Tournament,
+    a: usize,
+    b: usize,
+) -> Result<(), String> {
+    eprintln!("{} against {}", options.bots[a].0, options.bots[b].0);
+    for seed in options.first_seed..options.first_seed + options.seeds {
+        for (left, right) in [(a, b), (b, a)] {
+            let winner = play_game(options, left, right, seed)?;
+            tournament.record(left, right, winner);
+        }
+    }
+    Ok(())
+}
+
+fn play_round_robin(options: &Options, tournament: &mut Tournament) -> Result<(), String> {
+    let bots = options.bots.len();
+    for a in 0..bots {
+        for b in a + 1..bots {
+            play_pairing(options, tournament, a, b)?;
+        }
+    }
+    Ok(())
+}
+
+fn play_swiss(options: &Options, tournament: &mut Tournament) -> Result<(), String> {
+    let bots = options.bots.len();
+    // Halving the field every round finds a single leader
+    let rounds = options
+        .rounds
+        .unwrap_or_else(|| (usize::BITS - (bots - 1).leading_zeros()) as usize);
+    let mut met = HashSet::new();
+    let mut sat_out = HashSet::new();
+    for round in 1..=rounds {
+        eprintln!("Round {}", round);
+        let (pairs, bye) = swiss_pairs(&tournament.standings(), &met, &sat_out);
+        if let Some(bye) = bye {
+            // As if it drew every game
+            tournament.byes[bye] += options.seeds as f64;
+            sat_out.insert(bye);
+        }
+        for (a, b) in pairs {
+            met.insert((a.min(b), a.max(b)));
+            play_pairing(options, tournament, a, b)?;
+        }
+    }
+    Ok(())
+}
+
+// Neighbours in the standings play, unless they met already and someone
+// further down hasn't. With an odd number the lowest one that hasn't sat out
+// yet sits this round out.
+fn swiss_pairs(
+    standings: &[usize],
+    met: &HashSet<(usize, usize)>,
+    sat_out: &HashSet<usize>,
+) -> (Vec<(usize, usize)>, Option<usize>) {
+    let mut unpaired: Vec<usize> = standings.to_vec();
+    let bye = if unpaired.len() % 2 == 1 {
+        let index = unpaired
+            .iter()
+            .rposition(|bot| !sat_out.contains(bot))
+            .unwrap_or(unpaired.len() - 1);
+        Some(unpaired.remove(index))
+    } else {
+        None
+    };
+    let mut pairs = Vec::new();
+    while unpaired.len() > 1 {
+        let a = unpaired.remove(0);
+        let fresh = unpaired
+            .iter()
+            .position(|b| !met.contains(&(a.min(*b), a.max(*b))))
+            .unwrap_or(0);
+        pairs.push((a, unpaired.remove(fresh)));
+    }
+    (pairs, bye)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn options_parse() {
+        let options = parse(&args(&[
+            "--bot",
+            "greedy",
+            "--bot=greedy",
+            "--bot",
+            "random",
+            "--swiss",
+            "--seeds",
+            "3",
+            "--arena",
+            "20x10",
+        ]))
+        .unwrap();
+        let names: Vec<&str> = options.bots.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["greedy", "greedy#2", "random"]);
+        assert!(options.swiss);
+        assert_eq!(options.seeds, 3);
+        assert_eq!((options.level.width, options.level.height), (20, 10));
+
+        assert!(parse(&args(&["--bot", "greedy"])).is_err());
+        assert!(parse(&args(&[
+            "--bot", "greedy", "--bot", "random", "--seeds", "0"
+        ]))
+        .is_err());
+        assert!(parse(&args(&[
+            "--bot", "greedy", "--bot", "random", "--arena", "2x2"
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn ratings_move_by_the_same_amount() {
+        let mut tournament = Tournament::new(3);
+        tournament.record(0, 1, Some(0));
+        tournament.record(1, 2, None);
+        tournament.record(2, 0, Some(2));
+        let sum: f64 = tournament.ratings.iter().sum();
+        assert!((sum - 3.0 * START_RATING).abs() < 1e-9);
+        assert!(tournament.ratings[1] < START_RATING);
+
+        // Even players drawing stay even
+        let mut tournament = Tournament::new(2);
+        tournament.record(0, 1, None);
+        assert_eq!(tournament.ratings, [START_RATING, START_RATING]);
+    }
+
+    #[test]
+    fn records_count_both_sides() {
+        let mut tournament = Tournament::new(2);
+        tournament.record(0, 1, Some(0));
+        tournament.record(1, 0, Some(0));
+        tournament.record(0, 1, None);
+        let expected = Record {
+            wins: 2,
+            losses: 0,
+            draws: 1,
+        };
+        assert_eq!(tournament.records[&(0, 1)], expected);
+        assert_eq!(tournament.total(1).losses, 2);
+        assert_eq!(tournament.standings(), [0, 1]);
+    }
+
+    #[test]
+    fn swiss_avoids_rematches() {
+        let met = HashSet::from([(0, 1)]);
+        let (pairs, bye) = swiss_pairs(&[0, 1, 2, 3, 4], &met, &HashSet::new());
+        assert_eq!(pairs, [(0, 2), (1, 3)]);
+        assert_eq!(bye, Some(4));
+
+        // Everyone met everyone, neighbours play again
+        let met = HashSet::from([(0, 1), (0, 2), (1, 2)]);
+        let sat_out = HashSet::new();
+        assert_eq!(
+            swiss_pairs(&[2, 0, 1], &met, &sat_out),
+            (vec![(2, 0)], Some(1))
+        );
+    }
+
+    #[test]
+    fn swiss_byes_go_round() {
+        let sat_out = HashSet::from([4]);
+        let (pairs, bye) = swiss_pairs(&[0, 1, 2, 3, 4], &HashSet::new(), &sat_out);
+        assert_eq!(pairs, [(0, 1), (2, 4)]);
+        assert_eq!(bye, Some(3));
+    }
+
+    #[test]
+    fn round_robin_plays_every_pair_from_both_sides() {
+        let options = parse(&args(&[
+            "--bot", "greedy", "--bot", "random", "--bot", "cautious", "--seeds", "2", "--arena",
+            "10x10",
+        ]))
+        .unwrap();
+        let mut tournament = Tournament::new(3);
+        play_round_robin(&options, &mut tournament).unwrap();
+        for (me, them) in [(0, 1), (0, 2), (1, 2)] {
+            assert_eq!(tournament.records[&(me, them)].games(), 4);
+        }
+    }
+}
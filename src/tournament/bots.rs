@@ -0,0 +1,263 @@
+use super::game::{Game, DIRECTIONS};
+use crate::play_state::{arena::Position, snake::Direction};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+pub const BUILT_IN: [&str; 3] = ["random", "greedy", "cautious"];
+// A program that takes longer keeps going straight for that tick
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Picks the turn of one snake every tick
+pub trait Brain {
+    fn turn(&mut self, game: &Game, me: usize) -> Direction;
+
+    // Told how the game went once it is over
+    fn end(&mut self, _won: Option<bool>) {}
+}
+
+// A bot as given on the command line, a new brain is made for every game
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bot {
+    BuiltIn(&'static str),
+    // `exec:` and then the program with its arguments
+    Program(Vec<String>),
+}
+impl Bot {
+    pub fn parse(spec: &str) -> Result<Bot, String> {
+        if let Some(command) = spec.strip_prefix("exec:") {
+            let command: Vec<String> = command.split_whitespace().map(String::from).collect();
+            if command.is_empty() {
+                return Err("--bot exec: needs a program".to_string());
+            }
+            return Ok(Bot::Program(command));
+        }
+        match BUILT_IN.iter().find(|name| **name == spec) {
+            Some(name) => Ok(Bot::BuiltIn(name)),
+            None => Err(format!(
+                "--bot has no bot {}, pick one of {} or exec:<program>",
+                spec,
+                BUILT_IN.join(", ")
+            )),
+        }
+    }
+
+    pub fn brain(&self, game: &Game, me: usize, seed: u64) -> Result<Box<dyn Brain>, String> {
+        Ok(match self {
+            Bot::BuiltIn("random") => Box::new(Random(StdRng::seed_from_u64(seed ^ me as u64))),
+            Bot::BuiltIn("greedy") => Box::new(Greedy),
+            Bot::BuiltIn(_) => Box::new(Cautious),
+            Bot::Program(command) => Box::new(Program::start(command, game, me)?),
+        })
+    }
+}
+
+// Where a turn takes the head, and if it can go there at all
+fn safe_turns(game: &Game, me: usize) -> Vec<(Direction, Position)> {
+    let snake = &game.snakes[me];
+    DIRECTIONS
+        .into_iter()
+        .filter(|direction| *direction != snake.direction.opposite())
+        .map(|direction| (direction, snake.head() + direction.into()))
+        .filter(|(_, pos)| game.is_free(pos))
+        .collect()
+}
+
+fn distance(from: Position, to: Option<Position>) -> i32 {
+    to.map_or(0, |to| (from.x - to.x).abs() + (from.y - to.y).abs())
+}
+
+// Any turn that doesn't die right away
+struct Random(StdRng);
+impl Brain for Random {
+    fn turn(&mut self, game: &Game, me: usize) -> Direction {
+        match safe_turns(game, me).choose(&mut self.0) {
+            Some((direction, _)) => *direction,
+            None => game.snakes[me].direction,
+        }
+    }
+}
+
+// Straight for the food
+struct Greedy;
+impl Brain for Greedy {
+    fn turn(&mut self, game: &Game, me: usize) -> Direction {
+        safe_turns(game, me)
+            .into_iter()
+            .min_by_key(|(_, pos)| distance(*pos, game.food))
+            .map_or(game.snakes[me].direction, |(direction, _)| direction)
+    }
+}
+
+// For the food, but never into a pocket smaller than itself
+struct Cautious;
+impl Brain for Cautious {
+    fn turn(&mut self, game: &Game, me: usize) -> Direction {
+        let length = game.snakes[me].body.len();
+        safe_turns(game, me)
+            .into_iter()
+            .map(|(direction, pos)| (direction, pos, room(game, pos, length)))
+            .min_by_key(|(_, pos, room)| (room < &length, distance(*pos, game.food)))
+            .map_or(game.snakes[me].direction, |(direction, _, _)| direction)
+    }
+}
+
+// Free cells reachable from `start`, counting stops at `enough`
+fn room(game: &Game, start: Position, enough: usize) -> usize {
+    let mut seen = HashSet::from([start]);
+    let mut open = vec![start];
+    while let Some(pos) = open.pop() {
+        if seen.len() >= enough {
+            break;
+        }
+        for direction in DIRECTIONS {
+            let next = pos + direction.into();
+            if game.is_free(&next) && seen.insert(next) {
+                open.push(next);
+            }
+        }
+    }
+    seen.len()
+}
+
+// Any program talking over stdin and stdout, one line at a time. It is told
+// `snaky <width> <height> <players> <you>` and `walls x,y ...` when the game
+// starts, then `tick <n> <food> <snake> ...` every tick, where food is `x,y`
+// and every snake is `x,y;x,y;...` head first, or `-` for none. Row 0 is the
+// top. It answers `up`, `down`, `left` or `right`, and is told `end win`,
+// `end loss` or `end draw` when the game is over.
+struct Program {
+    child: Child,
+    stdin: ChildStdin,
+    answers: Receiver<String>,
+}
+impl Program {
+    fn start(command: &[String], game: &Game, me: usize) -> Result<Program, String> {
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("can't start {}: {}", command.join(" "), e))?;
+        let (stdin, stdout) = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => (stdin, stdout),
+            _ => panic!("HOW DID WE EVEN GET HERE!?!? A piped bot without pipes"),
+        };
+        // Read on a thread of its own so a stuck program can time out
+        let (sender, answers) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut program = Program {
+            child,
+            stdin,
+            answers,
+        };
+        let walls: Vec<String> = game.walls.iter().map(cell).collect();
+        program.say(&format!(
+            "snaky {} {} {} {}",
+            game.width,
+            game.height,
+            game.snakes.len(),
+            me
+        ));
+        program.say(format!("walls {}", walls.join(" ")).trim_end());
+        Ok(program)
+    }
+
+    // A program that went away just stops answering
+    fn say(&mut self, line: &str) {
+        let _ = writeln!(self.stdin, "{}", line);
+    }
+}
+impl Brain for Program {
+    fn turn(&mut self, game: &Game, me: usize) -> Direction {
+        let food = game.food.as_ref().map_or("-".to_string(), cell);
+        let snakes: Vec<String> = game
+            .snakes
+            .iter()
+            .map(|snake| {
+                if snake.is_alive() {
+                    snake.body.iter().map(cell).collect::<Vec<_>>().join(";")
+                } else {
+                    "-".to_string()
+                }
+            })
+            .collect();
+        // An answer too late for the last tick is not one for this tick
+        while self.answers.try_recv().is_ok() {}
+        self.say(&format!("tick {} {} {}", game.tick, food, snakes.join(" ")));
+        match self
+            .answers
+            .recv_timeout(ANSWER_TIMEOUT)
+            .as_deref()
+            .map(str::trim)
+        {
+            Ok("up") => Direction::Up,
+            Ok("down") => Direction::Down,
+            Ok("left") => Direction::Left,
+            Ok("right") => Direction::Right,
+            _ => game.snakes[me].direction,
+        }
+    }
+
+    fn end(&mut self, won: Option<bool>) {
+        self.say(match won {
+            Some(true) => "end win",
+            Some(false) => "end loss",
+            None => "end draw",
+        });
+    }
+}
+impl Drop for Program {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn cell(pos: &Position) -> String {
+    format!("{},{}", pos.x, pos.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play_state::level::Level;
+
+    #[test]
+    fn bots_parse() {
+        assert_eq!(Bot::parse("greedy"), Ok(Bot::BuiltIn("greedy")));
+        assert_eq!(
+            Bot::parse("exec:python3 bot.py"),
+            Ok(Bot::Program(vec![
+                "python3".to_string(),
+                "bot.py".to_string()
+            ]))
+        );
+        assert!(Bot::parse("exec:").is_err());
+        assert!(Bot::parse("clever").is_err());
+    }
+
+    #[test]
+    fn built_in_bots_keep_away_from_walls() {
+        let level = Level::default().resized(5, 5);
+        for name in BUILT_IN {
+            let mut game = Game::new(&level, 1, 3, 3);
+            let mut brain = Bot::BuiltIn(name).brain(&game, 0, 3).unwrap();
+            for _ in 0..10 {
+                let turn = brain.turn(&game, 0);
+                game.step(&[turn]);
+                assert!(game.snakes[0].is_alive(), "{} died", name);
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use crate::play_state::{
+    arena::Position, food::free_cell, level::Level, snake::Direction, GameRng,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// A game ends here when more than one snake is still going
+pub const MAX_TICKS: usize = 2000;
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Debug, Clone)]
+pub struct Snake {
+    // The head comes first
+    pub body: VecDeque<Position>,
+    pub direction: Direction,
+    // Tick it died on
+    pub died: Option<usize>,
+}
+impl Snake {
+    pub fn head(&self) -> Position {
+        self.body[0]
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.died.is_none()
+    }
+}
+
+// Any number of snakes on one level, played without bevy but by the classic
+// rules of the game: walls and bodies kill, food grows. Only plain levels are
+// played, see `is_plain`, and biting a body is always deadly. Food is placed
+// from the seed with the game's own `free_cell`, so a seed always plays out the
+// same for the same moves.
+#[derive(Debug)]
+pub struct Game {
+    pub width: i32,
+    pub height: i32,
+    pub walls: HashSet<Position>,
+    pub snakes: Vec<Snake>,
+    pub food: Option<Position>,
+    pub tick: usize,
+    rng: GameRng,
+}
+impl Game {
+    // Snakes start `length` long on rows spread over the arena, every other one
+    // on the right heading left. Walls where a snake starts are left out.
+    pub fn new(level: &Level, players: usize, length: usize, seed: u64) -> Game {
+        let (width, height) = (level.width, level.height);
+        let inset = (width / 4).max(length as i32 - 1);
+        let snakes: Vec<Snake> = (0..players)
+            .map(|index| {
+                let y = (index as i32 + 1) * height / (players as i32 + 1);
+                let (x, direction, trail) = if index % 2 == 0 {
+                    (inset, Direction::Right, -1)
+                } else {
+                    (width - 1 - inset, Direction::Left, 1)
+                };
+                Snake {
+                    body: (0..length as i32)
+                        .map(|part| Position {
+                            x: x + part * trail,
+                            y,
+                        })
+                        .collect(),
+                    direction,
+                    died: None,
+                }
+            })
+            .collect();
+        let walls = level
+            .walls
+            .iter()
+            .filter(|wall| !snakes.iter().any(|snake| snake.body.contains(wall)))
+            .copied()
+            .collect();
+        let mut game = Game {
+            width,
+            height,
+            walls,
+            snakes,
+            food: None,
+            tick: 0,
+            rng: GameRng::once(seed),
+        };
+        game.place_food();
+        game
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height
+    }
+
+    // Not a wall nor any living snake, and on the arena
+    pub fn is_free(&self, pos: &Position) -> bool {
+        self.contains(pos) && is_open(&self.walls, &self.snakes, pos)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.snakes.iter().filter(|snake| snake.is_alive()).count() <= 1 || self.tick >= MAX_TICKS
+    }
+
+    // The last one standing wins. When the rest went down together or time ran
+    // out, the longest of them wins and equal lengths are a draw.
+    pub fn winner(&self) -> Option<usize> {
+        let last = self
+            .snakes
+            .iter()
+            .map(|snake| snake.died.unwrap_or(usize::MAX))
+            .max()?;
+        let contenders: Vec<usize> = (0..self.snakes.len())
+            .filter(|index| self.snakes[*index].died.unwrap_or(usize::MAX) == last)
+            .collect();
+        let longest = contenders
+            .iter()
+            .map(|index| self.snakes[*index].body.len())
+            .max()?;
+        let mut longest = contenders
+            .into_iter()
+            .filter(|index| self.snakes[*index].body.len() == longest);
+        match (longest.next(), longest.next()) {
+            (Some(winner), None) => Some(winner),
+            _ => None,
+        }
+    }
+
+    // Every living snake moves at once, turning back is ignored. One turn per
+    // snake, dead ones included.
+    pub fn step(&mut self, turns: &[Direction]) {
+        let mut eaten = false;
+        for (snake, turn) in self.snakes.iter_mut().zip(turns) {
+            if !snake.is_alive() {
+                continue;
+            }
+            if *turn != snake.direction.opposite() {
+                snake.direction = *turn;
+            }
+            let head = snake.head() + snake.direction.into();
+            snake.body.push_front(head);
+            if Some(head) == self.food {
+                eaten = true;
+            } else {
+                snake.body.pop_back();
+            }
+        }
+
+        // Heads meeting count twice, so both of them die
+        let mut taken: HashMap<Position, usize> = HashMap::new();
+        for snake in self.snakes.iter().filter(|snake| snake.is_alive()) {
+            for pos in snake.body.iter() {
+                *taken.entry(*pos).or_default() += 1;
+            }
+        }
+        let tick = self.tick;
+        let (width, height) = (self.width, self.height);
+        for snake in self.snakes.iter_mut().filter(|snake| snake.is_alive()) {
+            let head = snake.head();
+            let outside = head.x < 0 || head.y < 0 || head.x >= width || head.y >= height;
+            if outside || self.walls.contains(&head) || taken[&head] > 1 {
+                snake.died = Some(tick);
+            }
+        }
+
+        if eaten {
+            self.food = None;
+            self.place_food();
+        }
+        self.tick += 1;
+    }
+
+    fn place_food(&mut self) {
+        let (walls, snakes) = (&self.walls, &self.snakes);
+        self.food = free_cell(&mut self.rng, self.width, self.height, |pos| {
+            is_open(walls, snakes, pos)
+        });
+    }
+}
+
+fn is_open(walls: &HashSet<Position>, snakes: &[Snake], pos: &Position) -> bool {
+    !walls.contains(pos)
+        && !snakes
+            .iter()
+            .any(|snake| snake.is_alive() && snake.body.contains(pos))
+}
+
+// Portals, hazards, keys and fixed food need the whole game to play
+pub fn is_plain(level: &Level) -> bool {
+    level.portals.is_empty()
+        && level.hazards.is_empty()
+        && level.keys.is_empty()
+        && level.food.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play_state::campaign::campaign_level;
+
+    const LENGTH: usize = 3;
+
+    fn level(width: i32, height: i32) -> Level {
+        Level::default().resized(width, height)
+    }
+
+    #[test]
+    fn snakes_start_inside_on_free_cells() {
+        for (width, height) in [(5, 5), (10, 7), (40, 30), (200, 150)] {
+            for players in 1..=4 {
+                let game = Game::new(&level(width, height), players, LENGTH, 0);
+                let cells: HashSet<Position> = game
+                    .snakes
+                    .iter()
+                    .flat_map(|snake| snake.body.clone())
+                    .collect();
+                assert_eq!(cells.len(), players * LENGTH);
+                assert!(cells.iter().all(|pos| game.contains(pos)));
+                assert!(matches!(game.food, Some(food) if !cells.contains(&food)));
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_food() {
+        let play = |seed| {
+            let mut game = Game::new(&level(20, 20), 2, LENGTH, seed);
+            let mut foods = Vec::new();
+            while !game.is_over() {
+                foods.push(game.food);
+                let turns: Vec<Direction> = game
+                    .snakes
+                    .iter()
+                    .map(|snake| {
+                        DIRECTIONS
+                            .into_iter()
+                            .find(|direction| game.is_free(&(snake.head() + (*direction).into())))
+                            .unwrap_or(snake.direction)
+                    })
+                    .collect();
+                game.step(&turns);
+            }
+            (foods, game.tick, game.winner())
+        };
+        assert_eq!(play(7), play(7));
+    }
+
+    #[test]
+    fn heads_meeting_is_a_draw() {
+        let mut game = Game::new(&level(9, 5), 2, LENGTH, 0);
+        // Both start on row 1 and row 3, bring them onto one row head to head
+        game.snakes[1].body = [(6, 1), (7, 1), (8, 1)]
+            .iter()
+            .map(|(x, y)| Position { x: *x, y: *y })
+            .collect();
+        game.food = None;
+        while !game.is_over() {
+            game.step(&[Direction::Right, Direction::Left]);
+        }
+        assert!(game.snakes.iter().all(|snake| !snake.is_alive()));
+        assert_eq!(game.winner(), None);
+    }
+
+    #[test]
+    fn only_plain_levels_are_played() {
+        assert!(is_plain(&Level::default()));
+        // Walls only, then one with portals
+        assert!(is_plain(&campaign_level(0)));
+        assert!(!is_plain(&campaign_level(2)));
+    }
+
+    #[test]
+    fn last_one_standing_wins() {
+        let mut game = Game::new(&level(10, 10), 2, LENGTH, 0);
+        game.food = None;
+        // The first one turns up and then runs into the left wall
+        game.step(&[Direction::Up, Direction::Left]);
+        while !game.is_over() {
+            game.step(&[Direction::Left, Direction::Left]);
+        }
+        assert!(!game.snakes[0].is_alive());
+        assert!(game.snakes[1].is_alive());
+        assert_eq!(game.winner(), Some(1));
+    }
+}